@@ -14,7 +14,7 @@ use bevy_inspector_egui::{
     bevy_egui::{EguiGlobalSettings, EguiPlugin, PrimaryEguiContext},
     quick::WorldInspectorPlugin,
 };
-use bevy_world_space_ui::{WorldSpaceUiPlugin, WorldSpaceUiRoot, WorldSpaceUiSurface};
+use bevy_world_space_ui::prelude::*;
 
 const WORLD_SPACE_UI_POINTER: PointerId =
     PointerId::Custom(Uuid::from_u128(235172396560254989313697768709775153593));
@@ -104,6 +104,7 @@ fn setup(
         .spawn((
             WorldSpaceUiRoot {
                 texture: image_handle.clone(),
+                ..default()
             },
             Node {
                 // Cover the whole image
@@ -125,11 +126,11 @@ fn setup(
     commands.spawn((
         Mesh3d(meshes.add(Rectangle::default())),
         WorldSpaceUiSurface {
-            root,
             texture: image_handle.clone(),
             pointer_id: WORLD_SPACE_UI_POINTER,
             ..default()
         },
+        UiSurfaceOf(root),
         Transform::from_xyz(0.0, 0.0, 1.5).with_rotation(Quat::from_axis_angle(Vec3::X, PI / 8.)),
     ));
 
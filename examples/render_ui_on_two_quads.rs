@@ -2,20 +2,8 @@
 //! `cargo run --example render_ui_on_two_quads`
 use std::f32::consts::PI;
 
-use bevy::{
-    asset::uuid::Uuid,
-    color::palettes::css::{BLUE, GRAY, GREEN, RED},
-    ecs::{lifecycle::HookContext, world::DeferredWorld},
-    picking::pointer::PointerId,
-    prelude::*,
-    render::render_resource::Extent3d,
-};
-use bevy_world_space_ui::{WorldSpaceUiPlugin, WorldSpaceUiRoot, WorldSpaceUiSurface};
-
-const WORLD_SPACE_UI_POINTER1: PointerId =
-    PointerId::Custom(Uuid::from_u128(235172396560254989313697768709775153593));
-const WORLD_SPACE_UI_POINTER2: PointerId =
-    PointerId::Custom(Uuid::from_u128(235172396560254989313697768709775153594));
+use bevy::{color::palettes::css::GRAY, prelude::*, render::render_resource::Extent3d};
+use bevy_world_space_ui::prelude::*;
 
 fn main() {
     App::new()
@@ -24,58 +12,6 @@ fn main() {
         .run();
 }
 
-/// Hoverable button UI component.
-#[derive(Component)]
-#[require(
-    Node {
-        position_type: PositionType::Absolute,
-        width: Val::Auto,
-        height: Val::Auto,
-        align_items: AlignItems::Center,
-        padding: UiRect::all(Val::Px(20.)),
-        border_radius: BorderRadius::all(Val::Px(10.)),
-        ..default()
-    },
-    BackgroundColor(BLUE.into()),
-)]
-#[component(on_add = HoverableButton::on_add)]
-pub struct HoverableButton {
-    pub on_click_message: String,
-}
-impl HoverableButton {
-    fn on_add(mut world: DeferredWorld, context: HookContext) {
-        world
-            .commands()
-            .entity(context.entity)
-            .observe(Self::on_over)
-            .observe(Self::on_out)
-            .observe(Self::on_click)
-            .observe(Self::on_release)
-            .with_child((
-                Text::new("Click me!"),
-                TextFont {
-                    font_size: FontSize::Px(40.0),
-                    ..default()
-                },
-                TextColor::WHITE,
-            ));
-    }
-    fn on_over(pointer: On<Pointer<Over>>, mut colors: Query<&mut BackgroundColor>) {
-        colors.get_mut(pointer.event().entity).unwrap().0 = RED.into();
-    }
-    fn on_release(pointer: On<Pointer<Release>>, mut colors: Query<&mut BackgroundColor>) {
-        colors.get_mut(pointer.event().entity).unwrap().0 = RED.into();
-    }
-    fn on_out(pointer: On<Pointer<Out>>, mut colors: Query<&mut BackgroundColor>) {
-        colors.get_mut(pointer.event().entity).unwrap().0 = BLUE.into();
-    }
-    fn on_click(pointer: On<Pointer<Press>>, mut query: Query<(&Self, &mut BackgroundColor)>) {
-        let (button, mut color) = query.get_mut(pointer.event().entity).unwrap();
-        *color = GREEN.into();
-        info!("{}", button.on_click_message);
-    }
-}
-
 fn setup(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
@@ -95,6 +31,7 @@ fn setup(
         .spawn((
             WorldSpaceUiRoot {
                 texture: image_handle1.clone(),
+                ..default()
             },
             Node {
                 // Cover the whole image
@@ -107,14 +44,17 @@ fn setup(
             },
             BackgroundColor(GRAY.into()),
         ))
-        .with_child(HoverableButton {
-            on_click_message: "Button clicked 1".to_string(),
+        .with_children(|parent| {
+            parent
+                .spawn(WorldSpaceUiButton::new("Click me!"))
+                .observe(|_: On<WorldSpaceUiButtonClick>| info!("Button clicked 1"));
         })
         .id();
     let root2 = commands
         .spawn((
             WorldSpaceUiRoot {
                 texture: image_handle2.clone(),
+                ..default()
             },
             Node {
                 // Cover the whole image
@@ -127,8 +67,10 @@ fn setup(
             },
             BackgroundColor(GRAY.into()),
         ))
-        .with_child(HoverableButton {
-            on_click_message: "Button clicked 2".to_string(),
+        .with_children(|parent| {
+            parent
+                .spawn(WorldSpaceUiButton::new("Click me!"))
+                .observe(|_: On<WorldSpaceUiButtonClick>| info!("Button clicked 2"));
         })
         .id();
 
@@ -136,11 +78,10 @@ fn setup(
     commands.spawn((
         Mesh3d(meshes.add(Rectangle::default())),
         WorldSpaceUiSurface {
-            root: root1,
             texture: image_handle1.clone(),
-            pointer_id: WORLD_SPACE_UI_POINTER1,
             ..default()
         },
+        UiSurfaceOf(root1),
         Transform::from_xyz(-1.0, 0.0, 1.5).with_rotation(Quat::from_axis_angle(Vec3::X, PI / 8.)),
     ));
 
@@ -148,11 +89,10 @@ fn setup(
     commands.spawn((
         Mesh3d(meshes.add(Rectangle::default())),
         WorldSpaceUiSurface {
-            root: root2,
             texture: image_handle2.clone(),
-            pointer_id: WORLD_SPACE_UI_POINTER2,
             ..default()
         },
+        UiSurfaceOf(root2),
         Transform::from_xyz(1.0, 0.0, 1.5).with_rotation(Quat::from_axis_angle(Vec3::X, PI / 8.)),
     ));
 
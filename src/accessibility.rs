@@ -0,0 +1,179 @@
+//! Places the UI nodes of world space roots in the window's accessibility tree, so screen
+//! readers can announce diegetic buttons. Each root becomes a pane, and node bounds are where
+//! the node appears on the root's surface as seen from the window's camera.
+use bevy::{
+    a11y::{
+        AccessibilityNode, AccessibilityRequested,
+        accesskit::{Node as AccessKitNode, Rect as AccessKitRect, Role},
+    },
+    camera::{Camera, Camera3d, NormalizedRenderTarget, RenderTarget, primitives::Aabb},
+    ecs::{
+        change_detection::Mut,
+        entity::Entity,
+        hierarchy::{ChildOf, Children},
+        name::Name,
+        query::{Added, Changed, Or, With, Without},
+        relationship::RelationshipTarget,
+        system::{Commands, Query, Res},
+    },
+    math::{Rect, Vec2},
+    transform::components::GlobalTransform,
+    ui::{ComputedNode, Node, UiGlobalTransform},
+    window::PrimaryWindow,
+};
+
+use crate::{
+    UiSurfaces, WorldSpaceUiRenderTarget, WorldSpaceUiRoot, WorldSpaceUiSurface, planar_point,
+};
+
+/// Whether an assistive technology requested the accessibility tree.
+pub(crate) fn accessibility_requested(requested: Option<Res<AccessibilityRequested>>) -> bool {
+    requested.is_some_and(|requested| requested.get())
+}
+
+/// Gives world space roots a pane in the accessibility tree, and their plain nodes a container,
+/// so the accessible nodes below them are nested under the pane instead of the window.
+pub(crate) fn add_accessibility_nodes(
+    mut commands: Commands,
+    roots: Query<(Entity, Option<&Name>), (With<WorldSpaceUiRoot>, Without<AccessibilityNode>)>,
+    new_nodes: Query<
+        Entity,
+        (
+            With<Node>,
+            Without<AccessibilityNode>,
+            Without<WorldSpaceUiRoot>,
+            Or<(Added<Node>, Changed<ChildOf>)>,
+        ),
+    >,
+    plain_nodes: Query<(), (With<Node>, Without<AccessibilityNode>)>,
+    world_space_roots: Query<(), With<WorldSpaceUiRoot>>,
+    parents: Query<&ChildOf>,
+    children: Query<&Children>,
+) {
+    let container = || AccessibilityNode(AccessKitNode::new(Role::GenericContainer));
+    for (root, name) in roots.iter() {
+        let mut pane = AccessKitNode::new(Role::Pane);
+        pane.set_label(name.map_or("World space UI", Name::as_str));
+        commands.entity(root).insert(AccessibilityNode(pane));
+        for node in children.iter_descendants(root) {
+            if plain_nodes.contains(node) {
+                commands.entity(node).insert(container());
+            }
+        }
+    }
+    for node in new_nodes.iter() {
+        if parents
+            .iter_ancestors(node)
+            .any(|ancestor| world_space_roots.contains(ancestor))
+        {
+            commands.entity(node).insert(container());
+        }
+    }
+}
+
+/// Sets the bounds of world space UI nodes to where they appear in the primary window, by
+/// projecting them onto the plane of the first surface of their root.
+pub(crate) fn update_accessibility_bounds(
+    roots: Query<(Entity, &UiSurfaces), With<WorldSpaceUiRoot>>,
+    surfaces: Query<
+        (&WorldSpaceUiRenderTarget, &GlobalTransform, &Aabb),
+        With<WorldSpaceUiSurface>,
+    >,
+    mut nodes: Query<(
+        &mut AccessibilityNode,
+        Option<&ComputedNode>,
+        Option<&UiGlobalTransform>,
+    )>,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &RenderTarget, &GlobalTransform), With<Camera3d>>,
+    children: Query<&Children>,
+) {
+    let Ok(window) = primary_window.single() else {
+        return;
+    };
+    let Some((camera, camera_transform)) = cameras
+        .iter()
+        .filter(|(camera, target, _)| {
+            camera.is_active
+                && matches!(
+                    target.normalize(Some(window)),
+                    Some(NormalizedRenderTarget::Window(target)) if target.entity() == window
+                )
+        })
+        .max_by_key(|(camera, ..)| camera.order)
+        .map(|(camera, _, transform)| (camera, transform))
+    else {
+        return;
+    };
+    let scale_factor = camera.target_scaling_factor().unwrap_or(1.0) as f64;
+    let viewport_origin = camera
+        .logical_viewport_rect()
+        .map_or(Vec2::ZERO, |rect| rect.min);
+
+    for (root, root_surfaces) in roots.iter() {
+        let Some((render_target, transform, aabb)) = root_surfaces
+            .iter()
+            .find_map(|surface| surfaces.get(surface).ok())
+        else {
+            continue;
+        };
+        // Physical window bounds of a region of the render target, in logical pixels like
+        // pointer positions.
+        let project = |region: Rect| {
+            let mut bounds = Rect::EMPTY;
+            for corner in [
+                region.min,
+                Vec2::new(region.max.x, region.min.y),
+                region.max,
+                Vec2::new(region.min.x, region.max.y),
+            ] {
+                let uv = render_target.uv(corner);
+                let point = planar_point(uv, transform, aabb);
+                let position = camera.world_to_viewport(camera_transform, point).ok()?;
+                bounds = bounds.union_point(position + viewport_origin);
+            }
+            Some(AccessKitRect {
+                x0: bounds.min.x as f64 * scale_factor,
+                y0: bounds.min.y as f64 * scale_factor,
+                x1: bounds.max.x as f64 * scale_factor,
+                y1: bounds.max.y as f64 * scale_factor,
+            })
+        };
+        // The pane covers the root's viewport.
+        let viewport = match render_target.viewport {
+            Some(viewport) => viewport.as_rect(),
+            None => Rect::from_corners(Vec2::ZERO, render_target.size.as_vec2()),
+        };
+        let surface_region = Rect {
+            min: viewport.min / render_target.scale_factor,
+            max: viewport.max / render_target.scale_factor,
+        };
+        if let Ok((accessible, ..)) = nodes.get_mut(root) {
+            set_bounds(accessible, project(surface_region));
+        }
+        for node in children.iter_descendants(root) {
+            let Ok((accessible, Some(computed), Some(node_transform))) = nodes.get_mut(node) else {
+                continue;
+            };
+            // Node layout is in physical pixels from the corner of the root's viewport.
+            let region = Rect::from_center_size(node_transform.translation, computed.size)
+                .intersect(Rect::from_corners(Vec2::ZERO, viewport.size()));
+            let region = Rect {
+                min: (region.min + viewport.min) / render_target.scale_factor,
+                max: (region.max + viewport.min) / render_target.scale_factor,
+            };
+            set_bounds(accessible, project(region));
+        }
+    }
+}
+
+/// Sets the bounds of an accessible node, only marking it changed if they differ.
+fn set_bounds(mut accessible: Mut<AccessibilityNode>, bounds: Option<AccessKitRect>) {
+    if accessible.bounds() == bounds {
+        return;
+    }
+    match bounds {
+        Some(bounds) => accessible.set_bounds(bounds),
+        None => accessible.clear_bounds(),
+    }
+}
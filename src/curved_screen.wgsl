@@ -0,0 +1,57 @@
+// Samples the UI texture through barrel distortion, like a CRT or curved cockpit screen.
+// Must match `WorldSpaceUiCurvature::apply`.
+#import bevy_pbr::{
+    pbr_fragment::pbr_input_from_standard_material,
+    pbr_functions::alpha_discard,
+}
+
+#ifdef PREPASS_PIPELINE
+#import bevy_pbr::{
+    prepass_io::{VertexOutput, FragmentOutput},
+    pbr_deferred_functions::deferred_output,
+}
+#else
+#import bevy_pbr::{
+    forward_io::{VertexOutput, FragmentOutput},
+    pbr_functions::{apply_pbr_lighting, main_pass_post_lighting_processing},
+    pbr_types::STANDARD_MATERIAL_FLAGS_UNLIT_BIT,
+}
+#endif
+
+@group(#{MATERIAL_BIND_GROUP}) @binding(100) var<uniform> curvature: vec2<f32>;
+
+fn curve(uv: vec2<f32>) -> vec2<f32> {
+    var centered = uv * 2.0 - 1.0;
+    centered = centered * (1.0 + curvature * centered.yx * centered.yx);
+    return centered * 0.5 + 0.5;
+}
+
+@fragment
+fn fragment(in: VertexOutput, @builtin(front_facing) is_front: bool) -> FragmentOutput {
+    var curved = in;
+#ifdef VERTEX_UVS_A
+    curved.uv = curve(in.uv);
+#endif
+    var pbr_input = pbr_input_from_standard_material(curved, is_front);
+
+#ifdef VERTEX_UVS_A
+    // Outside of the curved screen, like a bezel.
+    if any(curved.uv < vec2(0.0)) || any(curved.uv > vec2(1.0)) {
+        pbr_input.material.base_color = vec4(0.0, 0.0, 0.0, pbr_input.material.base_color.a);
+    }
+#endif
+    pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
+
+#ifdef PREPASS_PIPELINE
+    let out = deferred_output(in, pbr_input);
+#else
+    var out: FragmentOutput;
+    if (pbr_input.material.flags & STANDARD_MATERIAL_FLAGS_UNLIT_BIT) == 0u {
+        out.color = apply_pbr_lighting(pbr_input);
+    } else {
+        out.color = pbr_input.material.base_color;
+    }
+    out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+#endif
+    return out;
+}
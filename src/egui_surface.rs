@@ -0,0 +1,164 @@
+//! `egui` contexts rendered to world space UI textures and driven by surface pointers, e.g. for
+//! debug tools on in-world monitors. Requires the `egui` feature.
+use bevy::{
+    app::{App, Plugin, PreUpdate},
+    ecs::{
+        change_detection::DetectChanges,
+        component::Component,
+        entity::Entity,
+        message::{MessageReader, MessageWriter},
+        query::{Changed, With},
+        reflect::ReflectComponent,
+        schedule::IntoScheduleConfigs,
+        system::{Commands, Local, Query, Res, ResMut},
+    },
+    math::Vec2,
+    picking::pointer::{PointerAction, PointerButton, PointerInput},
+    reflect::{Reflect, std_traits::ReflectDefault},
+    ui::UiTargetCamera,
+};
+use bevy_egui::{
+    EguiContext, egui,
+    input::{EguiInputEvent, FocusedNonWindowEguiContext},
+};
+
+use crate::{
+    UiSurfaceOf, WorldSpaceUiFocus, WorldSpaceUiRenderTarget, WorldSpaceUiSurface,
+    WorldSpaceUiSurfaceHoverEnd, WorldSpaceUiSystems,
+};
+
+/// Drives `WorldSpaceUiEgui` roots. Add it after `EguiPlugin` and `WorldSpaceUiPlugin`.
+#[derive(Default)]
+pub struct WorldSpaceUiEguiPlugin;
+impl Plugin for WorldSpaceUiEguiPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<WorldSpaceUiEgui>().add_systems(
+            PreUpdate,
+            (add_egui_contexts, send_egui_input, focus_egui_context)
+                .chain()
+                .after(WorldSpaceUiSystems::SendInput),
+        );
+    }
+}
+
+/// Renders an `egui` context to the texture of this `WorldSpaceUiRoot`, over its UI nodes.
+/// The root's `UiTargetCamera` gets the `EguiContext`, so draw to it in `Update` through a
+/// `Query<&mut EguiContext>` with the camera entity.
+/// Pointers of the root's surfaces move and click in the context, and keyboard input goes to it
+/// while one of its surfaces has the `WorldSpaceUiFocus`.
+#[derive(Component, Debug, Clone, Default, Reflect)]
+#[reflect(Component, Default)]
+pub struct WorldSpaceUiEgui;
+
+/// Adds an `EguiContext` to the cameras of `WorldSpaceUiEgui` roots.
+fn add_egui_contexts(
+    mut commands: Commands,
+    roots: Query<&UiTargetCamera, (With<WorldSpaceUiEgui>, Changed<UiTargetCamera>)>,
+) {
+    for camera in roots.iter() {
+        commands
+            .entity(camera.0)
+            .insert_if_new(EguiContext::default());
+    }
+}
+
+/// Converts inputs to the virtual pointers of `WorldSpaceUiEgui` surfaces into egui input.
+fn send_egui_input(
+    mut pointer_inputs: MessageReader<PointerInput>,
+    mut hover_ends: MessageReader<WorldSpaceUiSurfaceHoverEnd>,
+    surfaces: Query<(
+        &WorldSpaceUiSurface,
+        &WorldSpaceUiRenderTarget,
+        &UiSurfaceOf,
+    )>,
+    roots: Query<&UiTargetCamera, With<WorldSpaceUiEgui>>,
+    mut egui_inputs: MessageWriter<EguiInputEvent>,
+) {
+    for input in pointer_inputs.read() {
+        let Some((render_target, context)) = surfaces
+            .iter()
+            .filter(|(surface, ..)| surface.pointer_id == input.pointer_id)
+            .find_map(|(_, render_target, surface_of)| {
+                Some((render_target, roots.get(surface_of.0).ok()?.0))
+            })
+        else {
+            continue;
+        };
+        // Egui lays out in logical pixels from the corner of the camera's viewport.
+        let offset = render_target.viewport.map_or(Vec2::ZERO, |viewport| {
+            viewport.min.as_vec2() / render_target.scale_factor
+        });
+        let position = input.location.position - offset;
+        let pos = egui::pos2(position.x, position.y);
+        let (button, pressed) = match input.action {
+            PointerAction::Move { .. } => {
+                egui_inputs.write(EguiInputEvent {
+                    context,
+                    event: egui::Event::PointerMoved(pos),
+                });
+                continue;
+            }
+            PointerAction::Cancel => {
+                egui_inputs.write(EguiInputEvent {
+                    context,
+                    event: egui::Event::PointerGone,
+                });
+                continue;
+            }
+            PointerAction::Press(button) => (button, true),
+            PointerAction::Release(button) => (button, false),
+            _ => continue,
+        };
+        let button = match button {
+            PointerButton::Primary => egui::PointerButton::Primary,
+            PointerButton::Secondary => egui::PointerButton::Secondary,
+            PointerButton::Middle => egui::PointerButton::Middle,
+        };
+        egui_inputs.write(EguiInputEvent {
+            context,
+            event: egui::Event::PointerButton {
+                pos,
+                button,
+                pressed,
+                modifiers: egui::Modifiers::default(),
+            },
+        });
+    }
+    // Hover highlights clear once no ray hits the surface.
+    for hover_end in hover_ends.read() {
+        let Some(camera) = surfaces
+            .get(hover_end.surface)
+            .ok()
+            .and_then(|(.., surface_of)| roots.get(surface_of.0).ok())
+        else {
+            continue;
+        };
+        egui_inputs.write(EguiInputEvent {
+            context: camera.0,
+            event: egui::Event::PointerGone,
+        });
+    }
+}
+
+/// Routes keyboard input to the egui context of the focused surface.
+fn focus_egui_context(
+    focus: Res<WorldSpaceUiFocus>,
+    surfaces: Query<&UiSurfaceOf>,
+    roots: Query<&UiTargetCamera, With<WorldSpaceUiEgui>>,
+    mut focused: ResMut<FocusedNonWindowEguiContext>,
+    mut last_focused: Local<Option<Entity>>,
+) {
+    if !focus.is_changed() {
+        return;
+    }
+    let context = focus
+        .0
+        .and_then(|surface| surfaces.get(surface).ok())
+        .and_then(|surface_of| roots.get(surface_of.0).ok())
+        .map(|camera| camera.0);
+    // Leave contexts the app focused itself alone.
+    if context.is_some() || focused.0 == *last_focused {
+        focused.0 = context;
+    }
+    *last_focused = context;
+}
@@ -0,0 +1,389 @@
+//! Picks surfaces by rendering their IDs and UVs under the cursor and reading them back,
+//! for `WorldSpaceUiRaycast::Gpu`.
+use bevy::{
+    asset::{Asset, Assets, Handle, RenderAssetUsages},
+    camera::{
+        Camera, Camera3d, ClearColorConfig, ImageRenderTarget, NormalizedRenderTarget, Projection,
+        RenderTarget, SubCameraView, visibility::RenderLayers,
+    },
+    color::Color,
+    core_pipeline::tonemapping::Tonemapping,
+    ecs::{
+        component::Component,
+        entity::Entity,
+        hierarchy::ChildOf,
+        lifecycle::HookContext,
+        message::MessageWriter,
+        name::Name,
+        observer::On,
+        query::{With, Without},
+        reflect::ReflectComponent,
+        resource::Resource,
+        system::{Commands, Local, Query, Res, ResMut},
+        world::DeferredWorld,
+    },
+    image::Image,
+    light::NotShadowCaster,
+    math::{UVec2, Vec2},
+    mesh::{Mesh3d, skinning::SkinnedMesh},
+    pbr::{Material, MeshMaterial3d, StandardMaterial},
+    picking::{Pickable, pointer::PointerInput},
+    reflect::{Reflect, std_traits::ReflectDefault},
+    render::{
+        gpu_readback::{Readback, ReadbackComplete},
+        render_resource::{AsBindGroup, Extent3d, TextureDimension, TextureFormat, TextureUsages},
+        view::{Hdr, Msaa},
+    },
+    shader::ShaderRef,
+    transform::components::{GlobalTransform, Transform},
+    utils::default,
+    window::{PrimaryWindow, Window},
+};
+
+use crate::{
+    HitWindow, PanelState, PreviousCursorPosition, WorldSpaceUiCurvature, WorldSpaceUiRaycast,
+    WorldSpaceUiRenderTarget, WorldSpaceUiSurface, hit_position, write_pointer_move,
+};
+
+/// Render layer of the picking camera and the ID meshes it draws.
+const GPU_PICKING_LAYER: usize = 30;
+
+const GPU_PICKING_SHADER: &str = "embedded://bevy_world_space_ui/gpu_picking.wgsl";
+
+/// Draws the mesh UVs and surface ID of a surface's mesh for the picking camera.
+#[derive(Asset, AsBindGroup, Debug, Clone, Default, Reflect)]
+pub(crate) struct SurfaceIdMaterial {
+    #[uniform(0)]
+    id: u32,
+}
+impl Material for SurfaceIdMaterial {
+    fn fragment_shader() -> ShaderRef {
+        GPU_PICKING_SHADER.into()
+    }
+}
+
+/// State of GPU picking.
+#[derive(Resource, Debug, Default)]
+pub(crate) struct GpuPicking {
+    camera: Option<Entity>,
+    /// Surfaces by ID minus one, since 0 is the cleared background. Slots of removed surfaces
+    /// are empty until their ID is reused.
+    surfaces: Vec<Option<Entity>>,
+    /// IDs of removed surfaces, reused before new ones are allocated.
+    free_ids: Vec<u32>,
+    /// Surface and mesh UV under the cursor, as of the last readback.
+    hit: Option<(Entity, Vec2)>,
+}
+impl GpuPicking {
+    /// Assigns an ID to a surface.
+    fn allocate(&mut self, surface: Entity) -> u32 {
+        match self.free_ids.pop() {
+            Some(id) => {
+                self.surfaces[id as usize - 1] = Some(surface);
+                id
+            }
+            None => {
+                self.surfaces.push(Some(surface));
+                self.surfaces.len() as u32
+            }
+        }
+    }
+
+    /// Frees the ID of a removed surface.
+    fn free(&mut self, id: u32, surface: Entity) {
+        let Some(slot) = id
+            .checked_sub(1)
+            .and_then(|index| self.surfaces.get_mut(index as usize))
+        else {
+            return;
+        };
+        // IDs loaded from scenes may belong to another surface.
+        if *slot != Some(surface) {
+            return;
+        }
+        *slot = None;
+        self.free_ids.push(id);
+        if self.hit.is_some_and(|(hit, _)| hit == surface) {
+            self.hit = None;
+        }
+    }
+}
+
+/// Marks the camera rendering the pixel under the cursor.
+#[derive(Component, Debug, Clone, Default, Reflect)]
+#[reflect(Component, Default)]
+pub(crate) struct GpuPickingCamera;
+
+/// Marks a surface with ID meshes for the picking camera, and holds its ID.
+#[derive(Component, Debug, Clone, Default, Reflect)]
+#[reflect(Component, Default)]
+#[component(on_remove = GpuPickingProxied::on_remove)]
+pub(crate) struct GpuPickingProxied {
+    id: u32,
+}
+impl GpuPickingProxied {
+    /// Frees the surface's ID and despawns its ID meshes.
+    fn on_remove(mut world: DeferredWorld, context: HookContext) {
+        let id = world.get::<Self>(context.entity).unwrap().id;
+        if let Some(mut picking) = world.get_resource_mut::<GpuPicking>() {
+            picking.free(id, context.entity);
+        }
+        let Some(proxies) = world
+            .get::<GpuPickingProxies>(context.entity)
+            .map(|proxies| proxies.0.clone())
+        else {
+            return;
+        };
+        for proxy in proxies {
+            world.commands().entity(proxy).try_despawn();
+        }
+    }
+}
+
+/// Makes this mesh draw the ID of a `GpuPickingProxied` surface for the picking camera.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+#[relationship(relationship_target = GpuPickingProxies)]
+pub(crate) struct GpuPickingProxyOf(pub Entity);
+
+/// All `GpuPickingProxyOf` meshes of a surface.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+#[relationship_target(relationship = GpuPickingProxyOf, linked_spawn)]
+pub(crate) struct GpuPickingProxies(Vec<Entity>);
+
+/// Spawns the picking camera, rendering one pixel into a texture that is read back every frame.
+pub(crate) fn spawn_gpu_picking_camera(
+    mut commands: Commands,
+    mut picking: ResMut<GpuPicking>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    if picking.camera.is_some() {
+        return;
+    }
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: 1,
+            height: 1,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0; 16],
+        TextureFormat::Rgba32Float,
+        RenderAssetUsages::default(),
+    );
+    image.texture_descriptor.usage |= TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC;
+    let image: Handle<Image> = images.add(image);
+    // IDs must not be blended, tonemapped, or averaged by multisampling.
+    let camera = commands
+        .spawn((
+            Name::new("GpuPickingCamera"),
+            GpuPickingCamera,
+            Camera3d::default(),
+            Camera {
+                clear_color: ClearColorConfig::Custom(Color::NONE),
+                is_active: false,
+                ..default()
+            },
+            RenderTarget::Image(ImageRenderTarget {
+                handle: image.clone(),
+                scale_factor: 1.0,
+            }),
+            Hdr,
+            Tonemapping::None,
+            Msaa::Off,
+            RenderLayers::layer(GPU_PICKING_LAYER),
+        ))
+        .id();
+    picking.camera = Some(camera);
+    commands
+        .spawn((Name::new("GpuPickingReadback"), Readback::texture(image)))
+        .observe(read_gpu_pick);
+}
+
+/// Decodes the pixel rendered by the picking camera.
+fn read_gpu_pick(
+    readback: On<ReadbackComplete>,
+    mut picking: ResMut<GpuPicking>,
+    cameras: Query<&Camera, With<GpuPickingCamera>>,
+) {
+    // The texture keeps the last pixel while the camera is off, e.g. with no cursor.
+    if picking
+        .camera
+        .and_then(|camera| cameras.get(camera).ok())
+        .is_none_or(|camera| !camera.is_active)
+    {
+        picking.hit = None;
+        return;
+    }
+    let channel = |i: usize| {
+        readback
+            .data
+            .get(4 * i..4 * i + 4)
+            .and_then(|bytes| bytes.try_into().ok())
+            .map_or(0.0, f32::from_le_bytes)
+    };
+    let (u, v, low, high) = (channel(0), channel(1), channel(2), channel(3));
+    let id = if high >= 1.0 {
+        (high as usize - 1) * 2048 + low as usize
+    } else {
+        0
+    };
+    // The cleared background, and IDs freed since the pixel was rendered, hit nothing.
+    picking.hit = match id.checked_sub(1) {
+        Some(index) => picking
+            .surfaces
+            .get(index)
+            .copied()
+            .flatten()
+            .map(|surface| (surface, Vec2::new(u, v))),
+        None => None,
+    };
+}
+
+/// Gives surface meshes a child drawing their ID for the picking camera.
+pub(crate) fn spawn_gpu_picking_proxies(
+    mut commands: Commands,
+    mut picking: ResMut<GpuPicking>,
+    surfaces: Query<
+        (Entity, &Mesh3d, Option<&SkinnedMesh>),
+        (With<WorldSpaceUiSurface>, Without<GpuPickingProxied>),
+    >,
+    mut materials: ResMut<Assets<SurfaceIdMaterial>>,
+) {
+    for (entity, mesh, skinned_mesh) in surfaces.iter() {
+        let id = picking.allocate(entity);
+        let material = materials.add(SurfaceIdMaterial { id });
+        commands.entity(entity).insert(GpuPickingProxied { id });
+        let mut proxy = commands.spawn((
+            Name::new("GpuPickingProxy"),
+            mesh.clone(),
+            MeshMaterial3d(material),
+            RenderLayers::layer(GPU_PICKING_LAYER),
+            Pickable::IGNORE,
+            NotShadowCaster,
+            GpuPickingProxyOf(entity),
+            ChildOf(entity),
+        ));
+        // Skinned surfaces are drawn with their current pose.
+        if let Some(skinned_mesh) = skinned_mesh {
+            proxy.insert(skinned_mesh.clone());
+        }
+    }
+}
+
+/// Points the picking camera at the primary window's cursor through its main `Camera3d`.
+pub(crate) fn sync_gpu_picking_camera(
+    picking: Res<GpuPicking>,
+    mode: Res<WorldSpaceUiRaycast>,
+    windows: Query<(Entity, &Window), With<PrimaryWindow>>,
+    main_cameras: Query<
+        (&Camera, &RenderTarget, &GlobalTransform, &Projection),
+        (With<Camera3d>, Without<GpuPickingCamera>),
+    >,
+    mut picking_camera: Query<
+        (
+            &mut Camera,
+            &mut Transform,
+            &mut GlobalTransform,
+            &mut Projection,
+        ),
+        With<GpuPickingCamera>,
+    >,
+) {
+    let Some(Ok((mut camera, mut transform, mut global_transform, mut projection))) =
+        picking.camera.map(|camera| picking_camera.get_mut(camera))
+    else {
+        return;
+    };
+    if *mode != WorldSpaceUiRaycast::Gpu {
+        camera.is_active = false;
+        return;
+    }
+    let Ok((window_entity, window)) = windows.single() else {
+        camera.is_active = false;
+        return;
+    };
+    let main_camera = main_cameras
+        .iter()
+        .filter(|(camera, target, ..)| {
+            camera.is_active
+                && matches!(
+                    target.normalize(Some(window_entity)),
+                    Some(NormalizedRenderTarget::Window(window)) if window.entity() == window_entity
+                )
+        })
+        .max_by_key(|(camera, ..)| camera.order);
+    let (Some((main_camera, _, main_transform, main_projection)), Some(cursor)) =
+        (main_camera, window.physical_cursor_position())
+    else {
+        camera.is_active = false;
+        return;
+    };
+    let viewport_offset = main_camera
+        .viewport
+        .as_ref()
+        .map_or(Vec2::ZERO, |viewport| viewport.physical_position.as_vec2());
+    let full_size = main_camera
+        .physical_viewport_size()
+        .unwrap_or_else(|| window.physical_size());
+    camera.is_active = true;
+    camera.sub_camera_view = Some(SubCameraView {
+        full_size,
+        offset: (cursor - viewport_offset).floor(),
+        size: UVec2::ONE,
+    });
+    *transform = main_transform.compute_transform();
+    *global_transform = *main_transform;
+    *projection = main_projection.clone();
+}
+
+/// Moves the virtual pointer of the surface under the cursor, as of the last readback.
+pub(crate) fn drive_gpu_pointer(
+    picking: Res<GpuPicking>,
+    mut last_hit: Local<Option<Entity>>,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+    mut surfaces: Query<(
+        &WorldSpaceUiSurface,
+        &WorldSpaceUiRenderTarget,
+        &mut PreviousCursorPosition,
+        &mut HitWindow,
+        Option<&WorldSpaceUiCurvature>,
+        Option<&MeshMaterial3d<StandardMaterial>>,
+        Option<&PanelState>,
+    )>,
+    materials: Res<Assets<StandardMaterial>>,
+    mut pointer_inputs: MessageWriter<PointerInput>,
+) {
+    // Window input stops going to the surface once the cursor leaves it.
+    let hit = picking.hit.map(|(entity, _)| entity);
+    if let Some(last) = last_hit.filter(|last| Some(*last) != hit)
+        && let Ok((.., mut hit_window, _, _, _)) = surfaces.get_mut(last)
+    {
+        hit_window.0 = None;
+    }
+    *last_hit = hit;
+    let Some((entity, uv)) = picking.hit else {
+        return;
+    };
+    let Ok((surface, render_target, mut cursor_last, mut hit_window, curvature, material, state)) =
+        surfaces.get_mut(entity)
+    else {
+        return;
+    };
+    if !PanelState::interactive(state) {
+        return;
+    }
+    let material = material.and_then(|material| materials.get(&material.0));
+    let Some(position) = hit_position(surface, render_target, uv, curvature, material) else {
+        return;
+    };
+    hit_window.0 = primary_window.single().ok();
+    write_pointer_move(
+        &mut pointer_inputs,
+        surface.pointer_id,
+        render_target,
+        &mut cursor_last,
+        position,
+    );
+}
@@ -0,0 +1,16 @@
+// Writes the mesh UV and surface ID of each pixel, for `WorldSpaceUiRaycast::Gpu`.
+// Must match the decoding in `read_gpu_pick`.
+#import bevy_pbr::forward_io::VertexOutput
+
+@group(#{MATERIAL_BIND_GROUP}) @binding(0) var<uniform> id: u32;
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+#ifdef VERTEX_UVS_A
+    let uv = in.uv;
+#else
+    let uv = vec2(0.0);
+#endif
+    // Split the ID so each part is exact in a half float target.
+    return vec4(uv, f32(id % 2048u), 1.0 + f32(id / 2048u));
+}
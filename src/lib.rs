@@ -1,232 +1,3399 @@
 //! Utilities for creating world space UIs in Bevy.
+use core::{f32::consts::FRAC_PI_2, ops::Range};
+
 use bevy::{
-    app::{App, First, Plugin},
-    asset::{Assets, Handle, RenderAssetUsages},
-    camera::{Camera, Camera2d, ClearColorConfig, NormalizedRenderTarget, RenderTarget},
-    color::Color,
+    a11y::AccessibilitySystems,
+    app::{App, First, Plugin, PostUpdate, PreUpdate},
+    asset::{
+        Asset, AssetEvent, AssetId, Assets, Handle, RenderAssetUsages, embedded_asset, uuid::Uuid,
+    },
+    camera::{
+        Camera, Camera2d, Camera3d, ClearColorConfig, ImageRenderTarget, NormalizedRenderTarget,
+        RenderTarget, Viewport,
+        primitives::Aabb,
+        visibility::{
+            InheritedVisibility, RenderLayers, ViewVisibility, Visibility, VisibilityRange,
+            VisibilitySystems,
+        },
+    },
+    color::{Alpha, Color, ColorToComponents, LinearRgba, Mix},
+    diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic},
     ecs::{
+        change_detection::{DetectChanges, DetectChangesMut, Ref},
         component::Component,
         entity::Entity,
         error::Result,
-        lifecycle::HookContext,
-        message::{MessageReader, MessageWriter},
+        event::EntityEvent,
+        hierarchy::{ChildOf, Children},
+        lifecycle::{HookContext, RemovedComponents},
+        message::{Message, MessageReader, MessageWriter},
         name::Name,
-        query::With,
-        schedule::IntoScheduleConfigs,
-        system::{Query, Res},
-        world::DeferredWorld,
+        observer::On,
+        query::{Added, Changed, Has, Or, With, Without},
+        reflect::{ReflectComponent, ReflectResource},
+        relationship::RelationshipTarget,
+        resource::Resource,
+        schedule::{IntoScheduleConfigs, SystemSet, common_conditions::resource_equals},
+        system::{Commands, EntityCommands, Local, Query, Res, ResMut, SystemParam},
+        world::{DeferredWorld, World},
     },
-    image::Image,
+    image::{Image, ImageFilterMode, ImageSampler, ImageSamplerDescriptor},
     input::{ButtonState, mouse::MouseButton},
-    math::{UVec2, Vec2},
-    mesh::Mesh3d,
-    pbr::{MeshMaterial3d, StandardMaterial},
+    log::{warn, warn_once},
+    math::{
+        Affine2, BVec2, Quat, Ray3d, Rect, URect, UVec2, Vec2, Vec2Swizzles, Vec3, Vec3A,
+        primitives::{Circle, Rectangle},
+    },
+    mesh::{Mesh, Mesh3d, MeshVertexBufferLayoutRef},
+    pbr::{
+        ExtendedMaterial, Material, MaterialExtension, MaterialExtensionKey,
+        MaterialExtensionPipeline, MaterialPlugin, MeshMaterial3d, StandardMaterial,
+    },
     picking::{
-        PickingSystems,
-        backend::ray::RayMap,
-        mesh_picking::ray_cast::{MeshRayCast, MeshRayCastSettings, RayCastVisibility},
-        pointer::{Location, PointerAction, PointerButton, PointerId, PointerInput},
+        Pickable, PickingSystems,
+        backend::ray::{RayId, RayMap},
+        hover::HoverMap,
+        mesh_picking::ray_cast::{
+            MeshRayCast, MeshRayCastSettings, RayCastBackfaces, RayCastVisibility,
+        },
+        pointer::{Location, PointerAction, PointerButton, PointerId, PointerInput, PointerPress},
+    },
+    platform::collections::{HashMap, HashSet},
+    reflect::{Reflect, std_traits::ReflectDefault},
+    render::alpha::AlphaMode,
+    render::{
+        render_resource::{
+            AsBindGroup, CompareFunction, Extent3d, Face, RenderPipelineDescriptor,
+            SpecializedMeshPipelineError, TextureDimension, TextureFormat, TextureUsages,
+        },
+        view::Msaa,
+    },
+    shader::ShaderRef,
+    tasks::ComputeTaskPool,
+    text::TextColor,
+    time::Time,
+    transform::{
+        TransformSystems,
+        components::{GlobalTransform, Transform},
+    },
+    ui::{
+        BackgroundColor, BorderColor, ComputedNode, GlobalZIndex, Interaction, Node, PositionType,
+        UiSystems, UiTargetCamera, Val,
+        widget::{ImageNode, Text},
     },
-    reflect::Reflect,
-    render::render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages},
-    ui::UiTargetCamera,
     utils::default,
-    window::{PrimaryWindow, WindowEvent},
+    window::{CursorIcon, PrimaryWindow, WindowEvent},
 };
 
+mod accessibility;
+#[cfg(feature = "egui")]
+mod egui_surface;
+mod gpu_picking;
+mod mipmaps;
+mod partial_redraw;
+mod ray_mesh;
+mod surface_2d;
+mod surface_index;
+pub mod ui_meshes;
+pub mod widgets;
+#[cfg(feature = "xr")]
+pub mod xr;
+#[cfg(feature = "egui")]
+pub use egui_surface::{WorldSpaceUiEgui, WorldSpaceUiEguiPlugin};
+use ray_mesh::{MeshHit, RayCast, RayMeshCache, invalidate_ray_mesh_cache, ray_mesh_hit};
+pub use surface_2d::WorldSpaceUiSurface2d;
+use surface_index::{SurfaceIndex, ray_hits_bounds};
+
+/// Commonly used types, for `use bevy_world_space_ui::prelude::*;`.
+pub mod prelude {
+    #[cfg(feature = "xr")]
+    pub use crate::xr::{WorldSpaceUiXrAim, WorldSpaceUiXrPlugin, XrHand};
+    pub use crate::{
+        AlwaysOnTop, AlwaysOnTopMaterial, AnchoredWorldSpaceUi, BackFaceMode, BudgetAction,
+        GeneratedPointerId, HoverCursorIcon, InjectPointerInput, PanelState, PanelTransition,
+        PartialRedraw, PauseWhenHidden, RecordPointerEvents, RenderOnDemand, ResizeWorldSpaceUi,
+        UiInteractionProxyOf, UiRefreshRate, UiSurfaceOf, UiSurfaces, UiTextureSettings, UvMode,
+        WorldSpaceUi, WorldSpaceUiBudgetEvent, WorldSpaceUiCommands, WorldSpaceUiCursor,
+        WorldSpaceUiCursorSprite, WorldSpaceUiCurvature, WorldSpaceUiDistanceFade,
+        WorldSpaceUiFade, WorldSpaceUiFocus, WorldSpaceUiFocusGained, WorldSpaceUiFocusLost,
+        WorldSpaceUiHighlight, WorldSpaceUiHit, WorldSpaceUiHitIndicator, WorldSpaceUiLazy,
+        WorldSpaceUiLod, WorldSpaceUiMaterial, WorldSpaceUiMaterialSettings, WorldSpaceUiPanel,
+        WorldSpaceUiPlugin, WorldSpaceUiPointerEvent, WorldSpaceUiPresses, WorldSpaceUiRayHit,
+        WorldSpaceUiRaycast, WorldSpaceUiResized, WorldSpaceUiRoot, WorldSpaceUiScene3d,
+        WorldSpaceUiSettings, WorldSpaceUiSurface, WorldSpaceUiSurface2d,
+        WorldSpaceUiSurfaceHoverEnd, WorldSpaceUiSurfaceHoverStart, WorldSpaceUiSurfacePress,
+        WorldSpaceUiSurfaceRelease, WorldSpaceUiSystems, WorldSpaceUiTransition, ui_meshes,
+        widgets::{ButtonColors, WorldSpaceUiButton, WorldSpaceUiButtonClick},
+    };
+    #[cfg(feature = "egui")]
+    pub use crate::{WorldSpaceUiEgui, WorldSpaceUiEguiPlugin};
+}
+
 /// Plugin supporting world space UI.
 #[derive(Default)]
 pub struct WorldSpaceUiPlugin;
 impl Plugin for WorldSpaceUiPlugin {
     fn build(&self, app: &mut App) {
-        app.register_type::<WorldSpaceUiRoot>()
-            .register_type::<WorldSpaceUiSurface>()
-            .add_systems(
-                First,
-                (drive_diegetic_pointer, send_pointer_input)
-                    .chain()
-                    .in_set(PickingSystems::Input),
-            );
+        embedded_asset!(app, "curved_screen.wgsl");
+        embedded_asset!(app, "gpu_picking.wgsl");
+        embedded_asset!(app, "mipmaps.wgsl");
+        app.add_plugins((
+            MaterialPlugin::<AlwaysOnTopMaterial>::default(),
+            MaterialPlugin::<CurvedScreenMaterial>::default(),
+            MaterialPlugin::<gpu_picking::SurfaceIdMaterial>::default(),
+            mipmaps::UiMipmapPlugin,
+            partial_redraw::PartialRedrawPlugin,
+        ))
+        .register_type::<WorldSpaceUiRoot>()
+        .register_type::<WorldSpaceUiSurface>()
+        .register_type::<GeneratedPointerId>()
+        .register_type::<WorldSpaceUiSurface2d>()
+        .register_type::<UiSurfaceOf>()
+        .register_type::<UiSurfaces>()
+        .register_type::<UiInteractionProxyOf>()
+        .register_type::<UiInteractionProxies>()
+        .register_type::<WorldSpaceUiRenderTarget>()
+        .register_type::<WorldSpaceUiMaterial>()
+        .register_type::<WorldSpaceUiMaterialSettings>()
+        .register_type::<UvMode>()
+        .register_type::<BackFaceMode>()
+        .register_type::<WorldSpaceUiFade>()
+        .register_type::<WorldSpaceUiDistanceFade>()
+        .register_type::<AlwaysOnTop>()
+        .register_type::<WorldSpaceUiCurvature>()
+        .register_type::<CurvedScreen>()
+        .register_type::<WorldSpaceUiPanel>()
+        .register_type::<AnchoredWorldSpaceUi>()
+        .register_type::<WorldSpaceUiHitIndicator>()
+        .register_type::<WorldSpaceUiHitIndicatorEntity>()
+        .register_type::<WorldSpaceUiCursorSprite>()
+        .register_type::<HoverCursorIcon>()
+        .register_type::<PanelState>()
+        .register_type::<PanelTransition>()
+        .register_type::<WorldSpaceUiTransition>()
+        .register_type::<WorldSpaceUiHighlight>()
+        .register_type::<widgets::WorldSpaceUiButton>()
+        .register_type::<WorldSpaceUiScene3d>()
+        .register_type::<WorldSpaceUiScene3dCamera>()
+        .register_type::<RenderOnDemand>()
+        .register_type::<PartialRedraw>()
+        .register_type::<UiRefreshRate>()
+        .register_type::<ShareUiCamera>()
+        .register_type::<WorldSpaceUiLazy>()
+        .register_type::<PauseWhenHidden>()
+        .register_type::<RecordPointerEvents>()
+        .register_type::<WorldSpaceUiLod>()
+        .register_type::<UiLodLow>()
+        .register_type::<UiLodPaused>()
+        .register_type::<UiBudgeted>()
+        .register_type::<TimeSinceRender>()
+        .register_type::<PreviousCursorPosition>()
+        .register_type::<HitWindow>()
+        .register_type::<LinkPending>()
+        .register_type::<CursorSpriteNode>()
+        .register_type::<HighlightFrame>()
+        .register_type::<gpu_picking::GpuPickingCamera>()
+        .register_type::<gpu_picking::GpuPickingProxied>()
+        .register_type::<gpu_picking::GpuPickingProxyOf>()
+        .register_type::<gpu_picking::GpuPickingProxies>()
+        .register_diagnostic(Diagnostic::new(WorldSpaceUiDiagnostics::RAYS_PROCESSED))
+        .register_diagnostic(Diagnostic::new(WorldSpaceUiDiagnostics::SURFACES_RAYCAST))
+        .register_diagnostic(Diagnostic::new(WorldSpaceUiDiagnostics::POINTER_EVENTS))
+        .register_diagnostic(Diagnostic::new(WorldSpaceUiDiagnostics::ACTIVE_UI_CAMERAS))
+        .register_diagnostic(
+            Diagnostic::new(WorldSpaceUiDiagnostics::TEXTURE_MEMORY).with_suffix(" MiB"),
+        )
+        .init_resource::<UiTexturePool>()
+        .init_resource::<SharedUiCameras>()
+        .init_resource::<gpu_picking::GpuPicking>()
+        .init_resource::<RayMeshCache>()
+        .init_resource::<WorldSpaceUiSettings>()
+        .register_type::<WorldSpaceUiSettings>()
+        .init_resource::<WorldSpaceUiRaycast>()
+        .register_type::<WorldSpaceUiRaycast>()
+        .register_type::<WorldSpaceUiHit>()
+        .init_resource::<WorldSpaceUiFocus>()
+        .register_type::<WorldSpaceUiFocus>()
+        .init_resource::<WorldSpaceUiPresses>()
+        .register_type::<WorldSpaceUiPresses>()
+        .add_message::<ResizeWorldSpaceUi>()
+        .add_message::<WorldSpaceUiResized>()
+        .add_message::<WorldSpaceUiBudgetEvent>()
+        .add_message::<PointerIdCollision>()
+        .add_message::<InjectPointerInput>()
+        .add_message::<WorldSpaceUiRayHit>()
+        .add_message::<WorldSpaceUiSurfaceHoverStart>()
+        .add_message::<WorldSpaceUiSurfaceHoverEnd>()
+        .add_message::<WorldSpaceUiFocusGained>()
+        .add_message::<WorldSpaceUiFocusLost>()
+        .add_message::<WorldSpaceUiPointerEvent>()
+        .add_observer(focus_pressed_surface)
+        .add_systems(First, link_pending_surfaces.before(PickingSystems::Input))
+        // Pointers are driven after bevy updates pointer rays from this frame's input, so button
+        // input is sent at the position the cursor had when it arrived. The virtual pointers'
+        // moves and presses are then received together, in order, next frame.
+        .configure_sets(
+            PreUpdate,
+            (
+                WorldSpaceUiSystems::DrivePointer,
+                WorldSpaceUiSystems::SendInput,
+            )
+                .chain()
+                .after(PickingSystems::ProcessInput)
+                .before(PickingSystems::Backend),
+        )
+        .add_systems(
+            PreUpdate,
+            (
+                drive_diegetic_pointer.run_if(resource_equals(WorldSpaceUiRaycast::Mesh)),
+                apply_external_ray_hits,
+                gpu_picking::drive_gpu_pointer.run_if(resource_equals(WorldSpaceUiRaycast::Gpu)),
+                surface_2d::drive_2d_pointer,
+                inject_pointer_input,
+            )
+                .chain()
+                .in_set(WorldSpaceUiSystems::DrivePointer),
+        )
+        .add_systems(
+            PreUpdate,
+            invalidate_ray_mesh_cache.before(WorldSpaceUiSystems::DrivePointer),
+        )
+        .add_systems(
+            PreUpdate,
+            (send_pointer_input, record_pointer_events)
+                .chain()
+                .in_set(WorldSpaceUiSystems::SendInput),
+        )
+        .add_systems(
+            PostUpdate,
+            (
+                resize_world_space_ui,
+                refresh_modified_textures,
+                detect_pointer_id_collisions,
+                detach_orphaned_surfaces,
+                dedup_ui_camera_orders,
+                sync_material_settings,
+                animate_surface_fades.after(sync_material_settings),
+                sync_curvature,
+                spawn_scene_3d_cameras.after(dedup_ui_camera_orders),
+                update_surface_lods.before(update_ui_camera_activity),
+                wake_lazy_world_space_ui.after(VisibilitySystems::CheckVisibility),
+                enforce_texture_budget
+                    .after(VisibilitySystems::CheckVisibility)
+                    .before(resize_world_space_ui)
+                    .before(update_ui_camera_activity),
+            ),
+        )
+        .add_systems(
+            PostUpdate,
+            (
+                update_ui_camera_activity
+                    .after(UiSystems::Layout)
+                    .after(VisibilitySystems::CheckVisibility),
+                partial_redraw::update_dirty_rects.after(update_ui_camera_activity),
+                measure_world_space_ui
+                    .after(update_ui_camera_activity)
+                    .after(schedule_shared_ui_cameras),
+            ),
+        )
+        .add_systems(
+            PostUpdate,
+            (
+                follow_anchors,
+                update_hit_indicators,
+                update_highlights,
+                animate_panel_transitions.before(animate_surface_fades),
+            )
+                .before(TransformSystems::Propagate),
+        )
+        .add_systems(
+            PostUpdate,
+            (
+                update_cursor_sprites.before(UiSystems::Layout),
+                sync_window_cursor_icons,
+                schedule_shared_ui_cameras
+                    .before(UiSystems::Prepare)
+                    .before(VisibilitySystems::VisibilityPropagate),
+            ),
+        )
+        .add_systems(
+            PostUpdate,
+            (
+                (
+                    gpu_picking::spawn_gpu_picking_camera,
+                    gpu_picking::spawn_gpu_picking_proxies,
+                )
+                    .run_if(resource_equals(WorldSpaceUiRaycast::Gpu)),
+                gpu_picking::sync_gpu_picking_camera.after(TransformSystems::Propagate),
+            )
+                .chain(),
+        )
+        .add_systems(
+            PostUpdate,
+            (
+                accessibility::add_accessibility_nodes,
+                accessibility::update_accessibility_bounds
+                    .after(UiSystems::Layout)
+                    .after(TransformSystems::Propagate),
+            )
+                .chain()
+                .run_if(accessibility::accessibility_requested)
+                .before(AccessibilitySystems::Update),
+        );
     }
 }
 
+/// Sets of the systems driving surface pointers in `PreUpdate`, to order your own input
+/// handling against them.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WorldSpaceUiSystems {
+    /// Moves virtual pointers to where rays, external hits, and injected input hit surfaces.
+    DrivePointer,
+    /// Forwards button input to virtual pointers and emits surface press events.
+    SendInput,
+}
+
 /// Marks the root node of a UI tree that is rendered to a texture for
 /// display in world space.
 /// This automatically spawns a render camera and adds a `UiTargetCamera` component.
+/// When saving scenes, exclude the spawned camera since it is recreated on load.
 #[derive(Component, Debug, Clone, Reflect)]
-#[component(on_add = WorldSpaceUiRoot::on_add)]
+#[reflect(Component, Default)]
+#[component(on_add = WorldSpaceUiRoot::on_add, on_remove = WorldSpaceUiRoot::on_remove)]
 pub struct WorldSpaceUiRoot {
     pub texture: Handle<Image>,
+    /// Camera used to render this root. It must render to `texture`.
+    /// If `None`, a camera is spawned automatically and despawned with the root.
+    #[entities]
+    pub camera: Option<Entity>,
+    /// Multisampling for the spawned camera. Unused if `camera` is provided.
+    pub msaa: Msaa,
+    /// Scale factor of the spawned camera's render target, like a window's DPI scale.
+    /// Values above 1 render the same layout larger. Unused if `camera` is provided.
+    pub scale_factor: f32,
+    /// Pixel region of `texture` this root renders into, for packing many roots into one atlas
+    /// texture. Surfaces of this root only display and interact with this region.
+    /// The texture isn't cleared by viewport cameras, so give the root an opaque background.
+    pub viewport: Option<URect>,
+    /// Render layers of the spawned camera, so it doesn't pick up other 2D content in the world.
+    /// UI nodes are routed by `UiTargetCamera` and don't need these layers.
+    /// Unused if `camera` is provided.
+    pub render_layers: RenderLayers,
+    /// Order of the spawned camera. Spawned cameras sharing a texture are offset from this
+    /// so their order is unambiguous. Unused if `camera` is provided.
+    pub order: isize,
+    /// Renders the UI at this multiple of its nominal resolution to improve legibility on
+    /// oblique surfaces. The texture must be allocated at the supersampled resolution,
+    /// which `WorldSpaceUiPanel` does automatically.
+    pub supersample: f32,
+    /// Called with the spawned camera, e.g. to add tonemapping or debug components.
+    /// Unused if `camera` is provided.
+    #[reflect(ignore)]
+    pub camera_setup: Option<fn(&mut EntityCommands)>,
+}
+impl Default for WorldSpaceUiRoot {
+    fn default() -> Self {
+        Self {
+            texture: Handle::default(),
+            camera: None,
+            msaa: Msaa::default(),
+            scale_factor: 1.0,
+            viewport: None,
+            render_layers: RenderLayers::layer(Self::DEFAULT_RENDER_LAYER),
+            // Render before the "main pass" camera
+            order: -1,
+            supersample: 1.0,
+            camera_setup: None,
+        }
+    }
 }
 impl WorldSpaceUiRoot {
+    /// Render layer used by spawned UI cameras by default.
+    pub const DEFAULT_RENDER_LAYER: usize = 31;
+
     /// Constructs a UI texture for rendering world space UI.
     pub fn get_ui_texture(resolution: Extent3d) -> Image {
+        Self::get_ui_texture_with_settings(resolution, &UiTextureSettings::default())
+    }
+
+    /// Constructs a UI texture for rendering world space UI with custom texture settings.
+    pub fn get_ui_texture_with_settings(
+        resolution: Extent3d,
+        settings: &UiTextureSettings,
+    ) -> Image {
         // This is the texture that will be rendered to.
+        let pixel = vec![0; settings.format.block_copy_size(None).unwrap_or_default() as usize];
         let mut image = Image::new_fill(
             resolution,
             TextureDimension::D2,
-            &[0, 0, 0, 0],
-            TextureFormat::Bgra8UnormSrgb,
+            &pixel,
+            settings.format,
             RenderAssetUsages::default(),
         );
-        // You need to set these texture usage flags in order to use the image as a render target
-        image.texture_descriptor.usage = TextureUsages::TEXTURE_BINDING
-            | TextureUsages::COPY_DST
-            | TextureUsages::RENDER_ATTACHMENT;
+        image.texture_descriptor.usage = settings.usages;
+        image.texture_descriptor.sample_count = settings.sample_count;
+        image.sampler = settings.sampler.clone();
+        if settings.mipmaps {
+            image.texture_descriptor.mip_level_count = mipmaps::mip_level_count(image.size());
+            // The render world fills every level, and initial data would need all of them.
+            image.data = None;
+        }
         image
     }
 
-    /// Automatically spawns a UI target camera and render target for the UI root.
+    /// Automatically spawns a UI target camera and render target for the UI root,
+    /// unless a camera was provided or the root is `WorldSpaceUiLazy`.
     fn on_add(mut world: DeferredWorld, context: HookContext) {
-        let root = world.entity(context.entity).components::<&Self>().clone();
-        let texture_camera = world
+        if world.entity(context.entity).contains::<WorldSpaceUiLazy>() {
+            // Dormant UI has no target camera, so keep it off the screen.
+            world
+                .commands()
+                .entity(context.entity)
+                .insert(Visibility::Hidden);
+            return;
+        }
+        Self::init(world, context.entity);
+    }
+
+    /// Spawns the UI target camera, unless a camera was provided.
+    fn init(mut world: DeferredWorld, entity: Entity) {
+        let root = world.entity(entity).components::<&Self>().clone();
+        let share = root.viewport.is_some() && world.entity(entity).contains::<ShareUiCamera>();
+        let shared = share
+            .then(|| {
+                world
+                    .resource::<SharedUiCameras>()
+                    .0
+                    .get(&root.texture.id())
+                    .copied()
+            })
+            .flatten();
+        let texture_camera = match root.camera.or(shared) {
+            Some(camera) => camera,
+            None => {
+                let mut commands = world.commands();
+                let mut camera = commands.spawn((
+                    Name::new("UiTargetCamera"),
+                    Camera2d,
+                    Camera {
+                        order: root.order,
+                        // Clearing would wipe other roots sharing the texture.
+                        clear_color: match root.viewport {
+                            Some(_) => ClearColorConfig::None,
+                            None => ClearColorConfig::Custom(Color::NONE),
+                        },
+                        viewport: root.viewport.map(|rect| Viewport {
+                            physical_position: rect.min,
+                            physical_size: rect.size(),
+                            ..default()
+                        }),
+                        ..default()
+                    },
+                    RenderTarget::Image(ImageRenderTarget {
+                        handle: root.texture.clone(),
+                        scale_factor: root.scale_factor * root.supersample,
+                    }),
+                    root.msaa,
+                    root.render_layers.clone(),
+                ));
+                if let Some(camera_setup) = root.camera_setup {
+                    camera_setup(&mut camera);
+                }
+                let camera = camera.id();
+                if share {
+                    world
+                        .resource_mut::<SharedUiCameras>()
+                        .0
+                        .insert(root.texture.id(), camera);
+                }
+                camera
+            }
+        };
+        world
             .commands()
-            .spawn((
-                Name::new("UiTargetCamera"),
-                Camera2d,
-                Camera {
-                    // Render before the "main pass" camera
-                    order: -1,
-                    clear_color: ClearColorConfig::Custom(Color::NONE),
-                    ..default()
-                },
-                RenderTarget::Image(root.texture.clone().into()),
-            ))
-            .id();
+            .entity(entity)
+            .insert(UiTargetCamera(texture_camera));
+    }
+
+    /// Despawns the UI camera spawned in `on_add`, unless other roots share it.
+    fn on_remove(mut world: DeferredWorld, context: HookContext) {
+        let root = world.get::<Self>(context.entity).unwrap();
+        if root.camera.is_some()
+            || (root.viewport.is_some() && world.entity(context.entity).contains::<ShareUiCamera>())
+        {
+            return;
+        }
+        let Some(texture_camera) = world.get::<UiTargetCamera>(context.entity).map(|c| c.0) else {
+            return;
+        };
+        world.commands().entity(texture_camera).try_despawn();
+    }
+}
+
+/// Defers the expensive setup of a `WorldSpaceUiRoot` or `WorldSpaceUiSurface` until a
+/// surface is first visible to a camera, so levels with many dormant terminals load without
+/// a camera, material, and virtual pointer each.
+/// A lazy surface gets its material and pointer once it is visible. A lazy root gets its
+/// camera once any of its surfaces is visible, and is `Visibility::Hidden` until then.
+/// Insert it together with the root or surface; it is removed when they wake up.
+#[derive(Component, Debug, Clone, Default, Reflect)]
+#[reflect(Component, Default)]
+pub struct WorldSpaceUiLazy;
+
+/// Renders a 3D scene underneath the UI of this `WorldSpaceUiRoot`, on the same texture,
+/// e.g. a security camera feed or a 3D item preview. A `Camera3d` is spawned once the root's
+/// UI camera exists, and the UI camera stops clearing the texture so the scene shows through.
+/// Pointer input still goes to the UI nodes drawn on top.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component, Default)]
+#[component(on_remove = WorldSpaceUiScene3d::on_remove)]
+pub struct WorldSpaceUiScene3d {
+    /// Initial transform of the scene camera. Move it through `WorldSpaceUiScene3dCamera`.
+    pub transform: Transform,
+    /// Layers rendered by the scene camera. The default layer renders the main world.
+    pub render_layers: RenderLayers,
+    pub clear_color: ClearColorConfig,
+}
+impl Default for WorldSpaceUiScene3d {
+    fn default() -> Self {
+        Self {
+            transform: Transform::default(),
+            render_layers: RenderLayers::default(),
+            clear_color: ClearColorConfig::Default,
+        }
+    }
+}
+impl WorldSpaceUiScene3d {
+    /// Despawns the scene camera.
+    fn on_remove(mut world: DeferredWorld, context: HookContext) {
+        let Some(camera) = world
+            .get::<WorldSpaceUiScene3dCamera>(context.entity)
+            .map(|camera| camera.0)
+        else {
+            return;
+        };
         world
             .commands()
             .entity(context.entity)
-            .insert(UiTargetCamera(texture_camera));
+            .remove::<WorldSpaceUiScene3dCamera>();
+        world.commands().entity(camera).try_despawn();
     }
 }
 
-/// Stores render target information for a `WorldSpaceUiSurface`.
-#[derive(Component, Debug, Clone)]
-pub struct WorldSpaceUiRenderTarget {
-    pub target: NormalizedRenderTarget,
+/// The camera spawned for a `WorldSpaceUiScene3d`.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct WorldSpaceUiScene3dCamera(pub Entity);
+
+/// Texture settings used when constructing a UI texture.
+#[derive(Debug, Clone)]
+pub struct UiTextureSettings {
+    /// Use e.g. `Rgba16Float` to render HDR UI that interacts with bloom.
+    pub format: TextureFormat,
+    /// You need at least `TEXTURE_BINDING | RENDER_ATTACHMENT` to use the image as a render target
+    /// displayed on a surface.
+    pub usages: TextureUsages,
+    /// Multisampled textures can't be sampled by `StandardMaterial` directly.
+    pub sample_count: u32,
+    /// Sampler used by materials displaying the texture.
+    pub sampler: ImageSampler,
+    /// Allocates a full mip chain, regenerated after each render of the UI camera, so text
+    /// doesn't shimmer when seen from afar. Requires a `sample_count` of 1 and a sampler with a
+    /// `mipmap_filter`, as in `UiTextureSettings::mipmapped`.
+    pub mipmaps: bool,
+}
+impl UiTextureSettings {
+    /// Settings with nearest filtering, for crisp pixel art UI.
+    pub fn nearest() -> Self {
+        Self {
+            sampler: ImageSampler::nearest(),
+            ..default()
+        }
+    }
+
+    /// Settings with anisotropic filtering up to `clamp` samples, for steeply angled panels.
+    /// `clamp` must be a power of two from 1 to 16.
+    pub fn anisotropic(clamp: u16) -> Self {
+        Self {
+            sampler: ImageSampler::Descriptor(ImageSamplerDescriptor {
+                anisotropy_clamp: clamp,
+                ..ImageSamplerDescriptor::linear()
+            }),
+            ..default()
+        }
+    }
+
+    /// Settings with a trilinear filtered mip chain, for panels seen from afar.
+    pub fn mipmapped() -> Self {
+        Self {
+            mipmaps: true,
+            sampler: ImageSampler::Descriptor(ImageSamplerDescriptor {
+                mipmap_filter: ImageFilterMode::Linear,
+                ..ImageSamplerDescriptor::linear()
+            }),
+            ..default()
+        }
+    }
+
+    /// Settings for an HDR texture, e.g. for `WorldSpaceUiMaterial::Hologram`.
+    pub fn hdr() -> Self {
+        Self {
+            format: TextureFormat::Rgba16Float,
+            ..default()
+        }
+    }
+}
+impl Default for UiTextureSettings {
+    fn default() -> Self {
+        Self {
+            format: TextureFormat::Bgra8UnormSrgb,
+            usages: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::RENDER_ATTACHMENT,
+            sample_count: 1,
+            sampler: ImageSampler::Default,
+            mipmaps: false,
+        }
+    }
+}
+
+/// Links a `WorldSpaceUiSurface` to the `WorldSpaceUiRoot` it displays.
+/// If the root is despawned, this is removed and the surface is detached.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+#[relationship(relationship_target = UiSurfaces)]
+pub struct UiSurfaceOf(pub Entity);
+
+/// All `WorldSpaceUiSurface`s displaying this `WorldSpaceUiRoot`.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+#[relationship_target(relationship = UiSurfaceOf)]
+pub struct UiSurfaces(Vec<Entity>);
+
+/// Makes this mesh receive pointer hits on behalf of a `WorldSpaceUiSurface`, using this mesh's
+/// UVs. Raycasts ignore the bind pose of skinned meshes, so for a skinned surface like a held
+/// tablet, parent a matching quad to the animated bone and link it here.
+/// The proxy needs no material, and the surface's own mesh is no longer raycast.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+#[relationship(relationship_target = UiInteractionProxies)]
+pub struct UiInteractionProxyOf(pub Entity);
+
+/// All `UiInteractionProxyOf` meshes of this `WorldSpaceUiSurface`.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+#[relationship_target(relationship = UiInteractionProxyOf)]
+pub struct UiInteractionProxies(Vec<Entity>);
+
+/// Only renders a `WorldSpaceUiRoot`'s camera on frames where its UI tree changed.
+/// Useful for static panels, since every active UI camera renders each frame.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component, Default)]
+pub struct RenderOnDemand {
+    dirty: bool,
+}
+impl Default for RenderOnDemand {
+    fn default() -> Self {
+        // Render at least once.
+        Self { dirty: true }
+    }
+}
+
+/// Only redraws the region of a `RenderOnDemand` root's texture covering the nodes that changed,
+/// e.g. for dashboards where a single number updates. The rest keeps its last render.
+/// Nodes are tracked by their bounds and outlines, so box shadows or text overflowing a node
+/// may leave stale pixels. Ignored by `ShareUiCamera` roots.
+#[derive(Component, Debug, Clone, Default, Reflect)]
+#[reflect(Component, Default)]
+#[require(RenderOnDemand)]
+#[component(on_remove = PartialRedraw::on_remove)]
+pub struct PartialRedraw {
+    /// Texture bounds of each node at its last render, in physical pixels.
+    #[reflect(ignore)]
+    drawn: HashMap<Entity, Rect>,
+    /// Viewport of the UI camera at its last render, redrawn in full when it changes.
+    #[reflect(ignore)]
+    viewport: Option<URect>,
+}
+impl PartialRedraw {
+    /// Lets the UI camera redraw its whole viewport again.
+    fn on_remove(mut world: DeferredWorld, context: HookContext) {
+        let Some(camera) = world
+            .get::<UiTargetCamera>(context.entity)
+            .map(|camera| camera.0)
+        else {
+            return;
+        };
+        world
+            .commands()
+            .entity(camera)
+            .try_remove::<partial_redraw::UiDirtyRect>();
+    }
+}
+
+/// Renders a `WorldSpaceUiRoot` with a `viewport` using one UI camera shared by every such
+/// root of the same texture, instead of spawning a camera per root, e.g. for hundreds of
+/// signs packed into an atlas. The shared camera renders one root's viewport per frame, in
+/// turn, so each root refreshes every N frames for N roots.
+/// A root with a hovered surface is rendered every frame until the pointer leaves, since UI
+/// picking only reaches the root being rendered. Other roots pause meanwhile.
+/// The viewports should all be the same size, so layouts don't change as the camera moves.
+/// The `Visibility` of sharing roots is managed by the scheduler, and they ignore
+/// `RenderOnDemand`, `UiRefreshRate`, `PauseWhenHidden`, `WorldSpaceUiScene3d`, and LODs.
+/// Insert it together with the root.
+#[derive(Component, Debug, Clone, Default, Reflect)]
+#[reflect(Component, Default)]
+pub struct ShareUiCamera;
+
+/// Shared UI cameras of `ShareUiCamera` roots, by texture.
+#[derive(Resource, Debug, Default)]
+struct SharedUiCameras(HashMap<AssetId<Image>, Entity>);
+
+/// Caps how often a `WorldSpaceUiRoot`'s camera renders, in Hz.
+/// Useful for far away or slowly updating panels.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+#[require(TimeSinceRender)]
+pub struct UiRefreshRate(pub f32);
+
+/// Seconds since a `UiRefreshRate` root last rendered.
+#[derive(Component, Debug, Clone, Default, Reflect)]
+#[reflect(Component, Default)]
+struct TimeSinceRender(f32);
+
+/// Stops rendering a `WorldSpaceUiRoot` while none of its surfaces are visible to any camera.
+/// Visibility is checked before deciding, so the root renders on the frame it comes into view.
+#[derive(Component, Debug, Clone, Default, Reflect)]
+#[reflect(Component, Default)]
+pub struct PauseWhenHidden;
+
+/// Sends a `WorldSpaceUiPointerEvent` for every input to the virtual pointer of a
+/// `WorldSpaceUiSurface`, e.g. for replays, tutorials, or telemetry.
+#[derive(Component, Debug, Clone, Default, Reflect)]
+#[reflect(Component, Default)]
+pub struct RecordPointerEvents;
+
+/// Swaps a `WorldSpaceUiSurface` to a cheap static `low_texture` while every camera is farther
+/// than `distance`, for scenes with many panels. A root stops rendering while all of its
+/// surfaces show their low texture, and low surfaces ignore pointers.
+/// The switch back happens `hysteresis` closer, so panels don't flicker at the threshold.
+/// Only owned materials are swapped.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct WorldSpaceUiLod {
+    pub low_texture: Handle<Image>,
+    pub distance: f32,
+    pub hysteresis: f32,
+}
+
+/// Marks a `WorldSpaceUiLod` surface showing its low texture.
+#[derive(Component, Debug, Clone, Default, Reflect)]
+#[reflect(Component, Default)]
+struct UiLodLow;
+
+/// Whether all surfaces of a `WorldSpaceUiRoot` show their `WorldSpaceUiLod` low texture.
+#[derive(Component, Debug, Clone, Default, Reflect)]
+#[reflect(Component, Default)]
+struct UiLodPaused(bool);
+
+/// UI changes that require a `RenderOnDemand` root to re-render.
+type UiChanged = Or<(
+    Changed<Node>,
+    Changed<ComputedNode>,
+    Changed<Children>,
+    Changed<BackgroundColor>,
+    Changed<BorderColor>,
+    Changed<ImageNode>,
+    Changed<Text>,
+    Changed<TextColor>,
+    Changed<Interaction>,
+)>;
+
+/// Request to reallocate the texture of a `WorldSpaceUiRoot` at a new resolution.
+/// Surfaces displaying the root are updated to match.
+#[derive(Message, Debug, Clone)]
+pub struct ResizeWorldSpaceUi {
+    pub root: Entity,
     pub size: UVec2,
 }
 
-/// Persists the previous cursor position on a `WorldSpaceUiSurface`.
-#[derive(Component, Debug, Clone, Default)]
-struct PreviousCursorPosition(pub Vec2);
+/// Emitted when `WorldSpaceUiSettings::texture_memory_budget` changes a root.
+#[derive(Message, Debug, Clone)]
+pub struct WorldSpaceUiBudgetEvent {
+    pub root: Entity,
+    pub action: BudgetAction,
+}
 
-/// Marks a mesh as a surface where UI will be rendered and interacted with.
+/// What the texture memory budget did to a root.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetAction {
+    /// The texture was halved in resolution. The layout is unchanged.
+    Downscaled { old: UVec2, new: UVec2 },
+    /// The texture was shrunk to a single pixel and the camera stopped rendering.
+    Deactivated,
+    /// The texture was reallocated at its original size and the camera resumed.
+    Restored,
+}
+
+/// Original size of a root changed by the texture memory budget, and whether it is
+/// deactivated.
 #[derive(Component, Debug, Clone, Reflect)]
-#[require(Mesh3d, PreviousCursorPosition)]
-#[component(on_add = WorldSpaceUiSurface::on_add)]
-pub struct WorldSpaceUiSurface {
+#[reflect(Component)]
+struct UiBudgeted {
+    original: UVec2,
+    deactivated: bool,
+}
+
+/// Emitted when a `ResizeWorldSpaceUi` reallocates a root's texture, e.g. to regenerate
+/// assets derived from it.
+#[derive(Message, Debug, Clone)]
+pub struct WorldSpaceUiResized {
     pub root: Entity,
-    pub texture: Handle<Image>,
+    pub old: UVec2,
+    pub new: UVec2,
+}
+
+/// Emitted when two distinct surfaces share the same `PointerId`.
+/// Hover and press state will be mixed between them until one is changed.
+#[derive(Message, Debug, Clone)]
+pub struct PointerIdCollision {
     pub pointer_id: PointerId,
-    pub default_material: Option<StandardMaterial>,
+    pub surfaces: [Entity; 2],
 }
-impl Default for WorldSpaceUiSurface {
+
+/// Request to drive a surface's virtual pointer directly, bypassing windows and raycasts.
+/// The pointer moves to `uv` before `action` is sent, which makes this suitable for
+/// simulating clicks in headless tests.
+#[derive(Message, Debug, Clone)]
+pub struct InjectPointerInput {
+    pub surface: Entity,
+    pub uv: Vec2,
+    pub action: PointerAction,
+}
+
+/// The nearest ray hit on a `WorldSpaceUiSurface` this frame, so other systems like crosshairs,
+/// lasers, or audio can tell where the user is pointing. Maintained from mesh raycasts and
+/// `WorldSpaceUiRayHit`s; removed when no ray hits the surface.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct WorldSpaceUiHit {
+    /// Pointer whose ray hit the surface, like the mouse.
+    pub pointer: PointerId,
+    /// UV of the hit on the surface's mesh.
+    pub uv: Vec2,
+    /// Position of the surface's virtual pointer on the render target.
+    pub position: Vec2,
+    pub world_position: Vec3,
+    pub normal: Vec3,
+    pub distance: f32,
+}
+
+/// Sent when a pointer's ray starts hitting a surface, e.g. to highlight a terminal.
+#[derive(Message, Debug, Clone)]
+pub struct WorldSpaceUiSurfaceHoverStart {
+    pub surface: Entity,
+    /// Pointer whose ray hit the surface, like the mouse, not the surface's virtual pointer.
+    pub pointer: PointerId,
+    /// UV of the hit on the surface's mesh.
+    pub uv: Vec2,
+    pub world_position: Vec3,
+}
+
+/// Sent when a pointer's ray stops hitting a surface it started hovering.
+#[derive(Message, Debug, Clone)]
+pub struct WorldSpaceUiSurfaceHoverEnd {
+    pub surface: Entity,
+    pub pointer: PointerId,
+}
+
+/// An input sent to the virtual pointer of a surface with `RecordPointerEvents`.
+#[derive(Message, Debug, Clone)]
+pub struct WorldSpaceUiPointerEvent {
+    pub surface: Entity,
+    /// UV of the pointer on the UI texture, from (0, 0) at the top left.
+    pub uv: Vec2,
+    pub action: PointerAction,
+}
+
+/// Triggered on a surface entity when a button is pressed while a ray hits it, whether or not
+/// a UI node is under its pointer, e.g. to power on a terminal with an observer.
+#[derive(EntityEvent, Debug, Clone)]
+pub struct WorldSpaceUiSurfacePress {
+    /// The surface.
+    pub entity: Entity,
+    pub button: PointerButton,
+    /// The nearest hit on the surface.
+    pub hit: WorldSpaceUiHit,
+}
+
+/// Triggered on a surface entity when a button is released while a ray hits it.
+#[derive(EntityEvent, Debug, Clone)]
+pub struct WorldSpaceUiSurfaceRelease {
+    /// The surface.
+    pub entity: Entity,
+    pub button: PointerButton,
+    /// The nearest hit on the surface.
+    pub hit: WorldSpaceUiHit,
+}
+
+/// The surface last pressed, e.g. to route keyboard input to it or dim other monitors.
+#[derive(Resource, Debug, Clone, Default, Reflect)]
+#[reflect(Resource, Default)]
+pub struct WorldSpaceUiFocus(pub Option<Entity>);
+
+/// The surface nearest to each pointer's ray when one of its buttons was pressed, until the
+/// button is released.
+#[derive(Resource, Debug, Clone, Default, Reflect)]
+#[reflect(Resource, Default)]
+pub struct WorldSpaceUiPresses(HashMap<(PointerId, PointerButton), Entity>);
+impl WorldSpaceUiPresses {
+    /// The surface holding a press of `button` by `pointer`, if any.
+    pub fn get(&self, pointer: PointerId, button: PointerButton) -> Option<Entity> {
+        self.0.get(&(pointer, button)).copied()
+    }
+
+    /// Iterates over presses in flight and the surfaces that received them.
+    pub fn iter(&self) -> impl Iterator<Item = (PointerId, PointerButton, Entity)> + '_ {
+        self.0
+            .iter()
+            .map(|(&(pointer, button), &surface)| (pointer, button, surface))
+    }
+}
+
+/// Sent when a surface gains `WorldSpaceUiFocus`.
+#[derive(Message, Debug, Clone)]
+pub struct WorldSpaceUiFocusGained {
+    pub surface: Entity,
+}
+
+/// Sent when a surface loses `WorldSpaceUiFocus` to another surface.
+#[derive(Message, Debug, Clone)]
+pub struct WorldSpaceUiFocusLost {
+    pub surface: Entity,
+}
+
+/// A ray hit on a surface from another raycasting backend, e.g. a physics engine, that moves
+/// the surface's pointer and sets its `WorldSpaceUiHit`. Surfaces stop receiving window input
+/// once a frame passes without hits on them.
+/// Set `WorldSpaceUiRaycast::External` to stop mesh raycasts.
+#[derive(Message, Debug, Clone)]
+pub struct WorldSpaceUiRayHit {
+    pub surface: Entity,
+    /// Pointer whose ray hit the surface, like the mouse.
+    pub pointer: PointerId,
+    /// UV of the hit on the surface's mesh, e.g. interpolated from the triangle a trimesh
+    /// collider built from the mesh reports. If `None`, the hit point is projected onto the
+    /// plane of the surface's AABB, which only suits flat surfaces.
+    pub uv: Option<Vec2>,
+    /// Hit point in world space.
+    pub point: Vec3,
+    pub normal: Vec3,
+    /// Distance from the ray's origin.
+    pub distance: f32,
+    /// Window whose button input is forwarded to the surface.
+    pub window: Option<Entity>,
+}
+
+/// Global defaults for world space UI. Insert it with the plugin to override them, e.g.
+/// `app.insert_resource(WorldSpaceUiSettings { nearest_hit_only: true, ..default() })`.
+#[derive(Resource, Debug, Clone, Reflect)]
+#[reflect(Resource, Default)]
+pub struct WorldSpaceUiSettings {
+    /// Settings for textures created by `WorldSpaceUi::build` without texture settings.
+    #[reflect(ignore)]
+    pub texture_settings: UiTextureSettings,
+    /// Which surfaces mesh raycasts consider, by visibility.
+    #[reflect(ignore)]
+    pub ray_cast_visibility: RayCastVisibility,
+    /// Only drive the nearest surface hit by each ray, instead of every surface along it.
+    pub nearest_hit_only: bool,
+    /// Hits farther than this are ignored, unless the surface has a `WorldSpaceUiDistanceFade`.
+    pub max_interaction_distance: f32,
+    /// Background surfaces are only raycast every this many frames, staggered so each frame
+    /// casts against a share of them. 1 casts against every surface every frame.
+    pub background_raycast_interval: u32,
+    /// Surfaces closer than this to a ray's origin, or hovered, are raycast every frame.
+    pub foreground_distance: f32,
+    /// Cast rays against surface meshes on the compute task pool, split across rays and
+    /// surfaces, instead of one at a time with `MeshRayCast`. Hits are handled in the same
+    /// order either way. Only triangle list meshes are hit.
+    pub parallel_raycast: bool,
+    /// Bytes of root textures allowed before the least recently viewed hidden roots are
+    /// downscaled, then deactivated, one per frame. Roots are restored once one of their
+    /// surfaces is visible again. See `WorldSpaceUiBudgetEvent`.
+    pub texture_memory_budget: Option<u64>,
+}
+impl Default for WorldSpaceUiSettings {
     fn default() -> Self {
         Self {
-            root: Entity::PLACEHOLDER,
-            texture: Handle::default(),
-            pointer_id: PointerId::default(),
-            default_material: None,
+            texture_settings: UiTextureSettings::default(),
+            ray_cast_visibility: RayCastVisibility::VisibleInView,
+            nearest_hit_only: false,
+            max_interaction_distance: f32::INFINITY,
+            background_raycast_interval: 1,
+            foreground_distance: 10.0,
+            parallel_raycast: false,
+            texture_memory_budget: None,
         }
     }
 }
-impl WorldSpaceUiSurface {
-    /// On component add, attach a MeshMaterial3d using the image
-    /// and spawn a UI camera and custom pointer.
-    fn on_add(mut world: DeferredWorld, context: HookContext) {
-        let surface = world.entity(context.entity).components::<&Self>().clone();
 
-        // This material has the texture that has been rendered.
-        let material_handle =
-            world
-                .resource_mut::<Assets<StandardMaterial>>()
-                .add(StandardMaterial {
-                    base_color_texture: Some(surface.texture.clone()),
-                    ..surface.default_material.unwrap_or_default()
-                });
+/// How surfaces are raycast to drive their pointers.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq, Reflect)]
+#[reflect(Resource, Default)]
+pub enum WorldSpaceUiRaycast {
+    /// Raycast surface meshes with `MeshRayCast` for every pointer ray.
+    #[default]
+    Mesh,
+    /// Only use `WorldSpaceUiRayHit`s sent by another backend.
+    External,
+    /// Render the surface IDs and mesh UVs under the primary window's cursor on the GPU and
+    /// read them back, for curved or skinned surfaces that raycasts handle poorly.
+    /// Hits arrive a frame or two late, other geometry doesn't occlude surfaces, and only the
+    /// virtual pointers are driven, without `WorldSpaceUiHit` or hover messages.
+    Gpu,
+}
 
-        let primary_window = world
-            .try_query_filtered::<Entity, With<PrimaryWindow>>()
-            .unwrap()
-            .single(&world)
-            .ok();
-        let ui_camera_entity = world.entity(surface.root).components::<&UiTargetCamera>().0;
-        let ui_camera = world.entity(ui_camera_entity).components::<&RenderTarget>();
-        let target = ui_camera.normalize(primary_window).unwrap();
-        let size = world
-            .resource::<Assets<Image>>()
-            .get(&surface.texture)
-            .unwrap()
-            .size();
+/// Stores render target information for a `WorldSpaceUiSurface`.
+/// This is recomputed whenever the surface is (re)linked, including after scene loads.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct WorldSpaceUiRenderTarget {
+    pub target: NormalizedRenderTarget,
+    pub size: UVec2,
+    /// Pixel region of the texture displayed by the surface, if it only shows its root's viewport.
+    pub viewport: Option<URect>,
+    /// Scale factor of the UI camera's image target, including supersampling.
+    /// Pointer positions are divided by it, since UI layout is in logical pixels.
+    pub scale_factor: f32,
+    /// See `WorldSpaceUiSurface::uv_rect`.
+    pub uv_rect: Rect,
+}
+impl WorldSpaceUiRenderTarget {
+    /// Converts a UV on the surface to a pointer position on the render target.
+    pub fn position(&self, uv: Vec2) -> Vec2 {
+        let uv = self.uv_rect.min + uv * self.uv_rect.size();
+        let pixel = match self.viewport {
+            Some(viewport) => viewport.min.as_vec2() + uv * viewport.size().as_vec2(),
+            None => self.size.as_vec2() * uv,
+        };
+        pixel / self.scale_factor
+    }
+
+    /// Converts a pointer position on the render target to a UV on the surface. Inverse of
+    /// `position`.
+    pub fn uv(&self, position: Vec2) -> Vec2 {
+        let pixel = position * self.scale_factor;
+        let uv = match self.viewport {
+            Some(viewport) => (pixel - viewport.min.as_vec2()) / viewport.size().as_vec2(),
+            None => pixel / self.size.as_vec2(),
+        };
+        (uv - self.uv_rect.min) / self.uv_rect.size()
+    }
+
+    /// Converts a UV on the texture, after any material UV transform, to a pointer position.
+    pub fn texture_position(&self, uv: Vec2) -> Vec2 {
+        self.size.as_vec2() * uv / self.scale_factor
+    }
+
+    /// Converts a pointer position to a UV on the texture. Inverse of `texture_position`.
+    pub fn texture_uv(&self, position: Vec2) -> Vec2 {
+        position * self.scale_factor / self.size.as_vec2()
+    }
+}
+
+/// Reads where the virtual pointers of surfaces are, e.g. to draw a custom crosshair or magnify
+/// the UI under the cursor. Positions are only returned while a ray hits the surface.
+#[derive(SystemParam)]
+pub struct WorldSpaceUiCursor<'w, 's> {
+    surfaces: Query<
+        'w,
+        's,
+        (
+            &'static WorldSpaceUiRenderTarget,
+            &'static PreviousCursorPosition,
+        ),
+        With<WorldSpaceUiHit>,
+    >,
+}
+impl WorldSpaceUiCursor<'_, '_> {
+    /// Position of the surface's pointer on its render target, in logical pixels like UI layout.
+    pub fn cursor_pixel(&self, surface: Entity) -> Option<Vec2> {
+        let (_, cursor) = self.surfaces.get(surface).ok()?;
+        Some(cursor.0)
+    }
+
+    /// UV of the surface's pointer on its UI texture, from (0, 0) at the top left.
+    pub fn cursor_uv(&self, surface: Entity) -> Option<Vec2> {
+        let (render_target, cursor) = self.surfaces.get(surface).ok()?;
+        Some(render_target.texture_uv(cursor.0))
+    }
+}
+
+/// Persists the previous cursor position on a `WorldSpaceUiSurface`.
+#[derive(Component, Debug, Clone, Default, Reflect)]
+#[reflect(Component, Default)]
+struct PreviousCursorPosition(pub Vec2);
+
+/// Tracks the window whose camera last cast a ray hitting a `WorldSpaceUiSurface`.
+/// Button input from other windows is not forwarded to the surface.
+#[derive(Component, Debug, Clone, Default, Reflect)]
+#[reflect(Component, Default)]
+struct HitWindow(pub Option<Entity>);
+
+/// Marks a `WorldSpaceUiSurface` that has not yet been linked to its root's render target,
+/// either because the root has no UI camera yet or the texture hasn't loaded.
+#[derive(Component, Debug, Clone, Default, Reflect)]
+#[reflect(Component, Default)]
+struct LinkPending;
 
+/// Material used to display the UI texture on a `WorldSpaceUiSurface`.
+#[derive(Debug, Clone, Reflect)]
+#[reflect(Default)]
+pub enum WorldSpaceUiMaterial {
+    /// Creates a new material per surface from this template, with the UI texture
+    /// as `base_color_texture`.
+    Template(StandardMaterial),
+    /// Uses an existing material as is, which may be shared between surfaces.
+    /// The material should already reference the UI texture.
+    Shared(Handle<StandardMaterial>),
+    /// Creates a glowing, additively blended material with the UI texture as emissive.
+    /// A `color` brighter than 1 blooms when viewed by an HDR camera.
+    Hologram { color: LinearRgba },
+    /// Doesn't create, insert, or modify any material, so a material you inserted yourself is
+    /// kept. Add `WorldSpaceUiCustomMaterial` to build another `Material` type from the UI texture.
+    Custom,
+}
+impl Default for WorldSpaceUiMaterial {
+    fn default() -> Self {
+        Self::Template(StandardMaterial::default())
+    }
+}
+impl WorldSpaceUiMaterial {
+    /// Returns true if the surface creates and owns its `StandardMaterial`.
+    pub fn is_owned(&self) -> bool {
+        matches!(self, Self::Template(_) | Self::Hologram { .. })
+    }
+
+    /// Builds the owned material displaying `texture`, or `None` for shared materials.
+    fn build(
+        &self,
+        texture: Option<Handle<Image>>,
+        settings: &WorldSpaceUiMaterialSettings,
+    ) -> Option<StandardMaterial> {
+        let mut material = match self {
+            Self::Template(template) => Some(StandardMaterial {
+                base_color_texture: texture,
+                ..template.clone()
+            }),
+            Self::Shared(_) | Self::Custom => None,
+            Self::Hologram { color } => Some(StandardMaterial {
+                // Only the texture's alpha is used from the base color.
+                base_color: Color::BLACK,
+                base_color_texture: texture.clone(),
+                emissive: *color,
+                emissive_texture: texture,
+                alpha_mode: AlphaMode::Add,
+                perceptual_roughness: 1.0,
+                reflectance: 0.0,
+                ..default()
+            }),
+        }?;
+        settings.apply(&mut material);
+        Some(material)
+    }
+}
+
+/// Builds a material of type `M` from the UI texture of this entity's `WorldSpaceUiSurface`,
+/// e.g. for custom screen shaders. Use with `WorldSpaceUiMaterial::Custom`.
+#[derive(Component)]
+#[component(on_add = custom_material_on_add::<M>)]
+pub struct WorldSpaceUiCustomMaterial<M: Material>(pub fn(Handle<Image>) -> M);
+
+/// Inserts the material built by `WorldSpaceUiCustomMaterial`.
+fn custom_material_on_add<M: Material>(mut world: DeferredWorld, context: HookContext) {
+    let build = world
+        .entity(context.entity)
+        .components::<&WorldSpaceUiCustomMaterial<M>>()
+        .0;
+    let Some(texture) = world
+        .get::<WorldSpaceUiSurface>(context.entity)
+        .map(|surface| surface.texture.clone())
+    else {
+        return;
+    };
+    let material = world.resource_mut::<Assets<M>>().add(build(texture));
+    world
+        .commands()
+        .entity(context.entity)
+        .insert(MeshMaterial3d(material));
+}
+
+/// Curves the UI of this entity's `WorldSpaceUiSurface` like a CRT or curved cockpit screen.
+/// The texture is sampled through barrel distortion of this strength per axis, and pointer
+/// UVs go through the same mapping so interaction stays pixel accurate.
+/// Use with `WorldSpaceUiMaterial::Custom`, since this inserts a `CurvedScreenMaterial`.
+#[derive(Component, Debug, Clone, Copy, Default, Reflect)]
+#[reflect(Component, Default)]
+#[component(on_add = WorldSpaceUiCurvature::on_add)]
+pub struct WorldSpaceUiCurvature(pub Vec2);
+impl WorldSpaceUiCurvature {
+    /// Maps a UV on the curved mesh to the UV of the texture displayed there.
+    /// The result is outside of [0, 1] near the corners, where nothing is displayed.
+    pub fn apply(&self, uv: Vec2) -> Vec2 {
+        let centered = uv * 2.0 - Vec2::ONE;
+        let curved = centered * (Vec2::ONE + self.0 * centered.yx() * centered.yx());
+        curved * 0.5 + Vec2::splat(0.5)
+    }
+
+    /// On component add, insert a `CurvedScreenMaterial` displaying the surface texture.
+    fn on_add(mut world: DeferredWorld, context: HookContext) {
+        let curvature = world.entity(context.entity).components::<&Self>().0;
+        let Some(surface) = world.get::<WorldSpaceUiSurface>(context.entity).cloned() else {
+            return;
+        };
+        if !matches!(surface.material, WorldSpaceUiMaterial::Custom) {
+            warn!(
+                "WorldSpaceUiCurvature on {} needs WorldSpaceUiMaterial::Custom",
+                context.entity
+            );
+        }
+        let material = world
+            .resource_mut::<Assets<CurvedScreenMaterial>>()
+            .add(ExtendedMaterial {
+                base: StandardMaterial {
+                    base_color_texture: Some(surface.texture),
+                    ..default()
+                },
+                extension: CurvedScreen { curvature },
+            });
         world
             .commands()
             .entity(context.entity)
-            .insert(MeshMaterial3d(material_handle))
-            .insert(WorldSpaceUiRenderTarget { target, size });
+            .insert(MeshMaterial3d(material));
+    }
+}
 
-        // Spawn a virtual pointer so we can send events to the rendered UI.
-        world.commands().spawn(surface.pointer_id);
+/// Material extension sampling the UI texture through barrel distortion.
+/// Inserted by `WorldSpaceUiCurvature`.
+#[derive(Asset, AsBindGroup, Debug, Clone, Default, Reflect)]
+pub struct CurvedScreen {
+    #[uniform(100)]
+    pub curvature: Vec2,
+}
+impl MaterialExtension for CurvedScreen {
+    fn fragment_shader() -> ShaderRef {
+        CURVED_SCREEN_SHADER.into()
+    }
+
+    fn deferred_fragment_shader() -> ShaderRef {
+        CURVED_SCREEN_SHADER.into()
     }
 }
 
-/// Because bevy has no way to know how to map a mouse input to the UI texture, we need to write a
-/// system that tells it there is a pointer on the UI texture. We cast a ray into the scene and find
-/// the UV (2D texture) coordinates of the raycast hit. This UV coordinate is effectively the same
-/// as a pointer coordinate on a 2D UI rect.
-fn drive_diegetic_pointer(
-    mut raycast: MeshRayCast,
-    rays: Res<RayMap>,
-    surfaces_check: Query<Entity, With<WorldSpaceUiSurface>>,
-    mut surfaces: Query<(
-        &WorldSpaceUiSurface,
-        &WorldSpaceUiRenderTarget,
-        &mut PreviousCursorPosition,
-    )>,
-    mut pointer_inputs: MessageWriter<PointerInput>,
-) -> Result {
-    // Find raycast hits and update the virtual pointer.
-    let raycast_settings = MeshRayCastSettings {
-        visibility: RayCastVisibility::VisibleInView,
-        filter: &|entity| surfaces_check.contains(entity),
-        early_exit_test: &|_| false,
-    };
-    let mut hit_pointer_ids = Vec::new();
-
-    for (_id, ray) in rays.iter() {
-        for (cube, hit) in raycast.cast_ray(*ray, &raycast_settings) {
-            let (surface, render_target, mut cursor_last) = surfaces.get_mut(*cube)?;
-            hit_pointer_ids.push(surface.pointer_id);
-            let position = render_target.size.as_vec2() * hit.uv.unwrap();
-            if position != cursor_last.0 {
-                pointer_inputs.write(PointerInput::new(
-                    surface.pointer_id,
-                    Location {
-                        target: render_target.target.clone(),
-                        position,
-                    },
-                    PointerAction::Move {
-                        delta: position - cursor_last.0,
-                    },
-                ));
-                cursor_last.0 = position;
-            }
+/// `StandardMaterial` displaying the UI texture on a curved screen.
+pub type CurvedScreenMaterial = ExtendedMaterial<StandardMaterial, CurvedScreen>;
+
+const CURVED_SCREEN_SHADER: &str = "embedded://bevy_world_space_ui/curved_screen.wgsl";
+
+/// Material extension that draws over the rest of the scene, ignoring and not writing depth,
+/// for HUD-like diegetic elements. Use with `WorldSpaceUiCustomMaterial(AlwaysOnTop::material)`.
+/// It relies on blending, since opaque meshes drawn later would overwrite it.
+#[derive(Asset, AsBindGroup, Debug, Clone, Default, Reflect)]
+pub struct AlwaysOnTop {}
+impl AlwaysOnTop {
+    /// Builds an unlit, blended material displaying `texture` on top of the scene.
+    pub fn material(texture: Handle<Image>) -> AlwaysOnTopMaterial {
+        ExtendedMaterial {
+            base: StandardMaterial {
+                base_color_texture: Some(texture),
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                ..default()
+            },
+            extension: AlwaysOnTop {},
+        }
+    }
+}
+impl MaterialExtension for AlwaysOnTop {
+    fn specialize(
+        _pipeline: &MaterialExtensionPipeline,
+        descriptor: &mut RenderPipelineDescriptor,
+        _layout: &MeshVertexBufferLayoutRef,
+        _key: MaterialExtensionKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        if let Some(depth_stencil) = descriptor.depth_stencil.as_mut() {
+            depth_stencil.depth_write_enabled = false;
+            depth_stencil.depth_compare = CompareFunction::Always;
         }
+        Ok(())
     }
+}
 
-    Ok(())
+/// `StandardMaterial` drawn on top of the scene.
+pub type AlwaysOnTopMaterial = ExtendedMaterial<StandardMaterial, AlwaysOnTop>;
+
+/// Material settings of a `WorldSpaceUiSurface`, applied on top of its owned material.
+/// Unset fields keep the material's value. Shared materials are never modified.
+#[derive(Debug, Clone, Default, Reflect)]
+#[reflect(Default)]
+pub struct WorldSpaceUiMaterialSettings {
+    /// `Opaque` and `Mask` write depth and sort correctly against everything.
+    /// `Blend` doesn't write depth and is sorted by distance against other transparent
+    /// meshes, which `depth_bias` can nudge. To always draw on top, see `AlwaysOnTop`.
+    pub alpha_mode: Option<AlphaMode>,
+    pub unlit: Option<bool>,
+    /// Disables back face culling and lights both sides.
+    pub double_sided: Option<bool>,
+    /// Pulls the surface toward the camera in depth, so panels mounted flush against walls
+    /// don't z-fight with them. Try values around 1000 before moving the mesh off the wall
+    /// with `WorldSpaceUiSurface::normal_offset`.
+    pub depth_bias: Option<f32>,
+}
+impl WorldSpaceUiMaterialSettings {
+    /// Applies the set fields to `material`.
+    pub fn apply(&self, material: &mut StandardMaterial) {
+        if let Some(alpha_mode) = self.alpha_mode {
+            material.alpha_mode = alpha_mode;
+        }
+        if let Some(unlit) = self.unlit {
+            material.unlit = unlit;
+        }
+        if let Some(double_sided) = self.double_sided {
+            material.double_sided = double_sided;
+            material.cull_mode = (!double_sided).then_some(Face::Back);
+        }
+        if let Some(depth_bias) = self.depth_bias {
+            material.depth_bias = depth_bias;
+        }
+    }
 }
 
-/// Send pointer pressed and released events to the world space UI.
+/// Animates the tint of a `WorldSpaceUiSurface`'s owned material from `from` to `to`
+/// over `duration` seconds, e.g. to fade holograms in and out.
+/// The tint multiplies the material's base and emissive colors, and opaque materials are
+/// blended while the tint is translucent. Shared and custom materials are not modified.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct WorldSpaceUiFade {
+    pub from: Color,
+    pub to: Color,
+    pub duration: f32,
+    pub elapsed: f32,
+}
+impl WorldSpaceUiFade {
+    /// Fades from transparent to the material's original colors.
+    pub fn fade_in(duration: f32) -> Self {
+        Self {
+            from: Color::WHITE.with_alpha(0.0),
+            to: Color::WHITE,
+            duration,
+            elapsed: 0.0,
+        }
+    }
+
+    /// Fades from the material's original colors to transparent.
+    pub fn fade_out(duration: f32) -> Self {
+        Self {
+            from: Color::WHITE,
+            to: Color::WHITE.with_alpha(0.0),
+            duration,
+            elapsed: 0.0,
+        }
+    }
+
+    /// Returns true once the tint has reached `to`.
+    pub fn finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    /// Current tint of the animation.
+    pub fn tint(&self) -> LinearRgba {
+        let t = if self.duration > 0.0 {
+            (self.elapsed / self.duration).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        self.from.to_linear().mix(&self.to.to_linear(), t)
+    }
+}
+
+/// Whether a surface is open. Surfaces only receive pointer input while `Open`, so menus
+/// can't be clicked mid-animation. Set `Opening` or `Closing` to animate a
+/// `WorldSpaceUiTransition`.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default)]
+pub enum PanelState {
+    Opening,
+    #[default]
+    Open,
+    Closing,
+    Closed,
+}
+impl PanelState {
+    /// Returns true if a surface in this state receives pointer input.
+    pub fn interactive(state: Option<&Self>) -> bool {
+        state.is_none_or(|state| *state == Self::Open)
+    }
+}
+
+/// Animation of a `WorldSpaceUiTransition`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect)]
+#[reflect(Default)]
+pub enum PanelTransition {
+    /// Scales the surface in from its origin.
+    #[default]
+    Scale,
+    /// Collapses the surface vertically, like a CRT switching off.
+    Wipe,
+    /// Fades the surface's owned material with a `WorldSpaceUiFade`.
+    Dissolve,
+}
+
+/// Animates a surface open and closed as its `PanelState` changes. The surface is hidden
+/// while closed. `Scale` and `Wipe` scale the `Transform` captured when this is added.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component, Default)]
+#[require(PanelState)]
+#[component(on_add = WorldSpaceUiTransition::on_add)]
+pub struct WorldSpaceUiTransition {
+    pub transition: PanelTransition,
+    /// Seconds to fully open or close.
+    pub duration: f32,
+    /// How open the surface is, from 0 to 1.
+    progress: f32,
+    base_scale: Vec3,
+}
+impl Default for WorldSpaceUiTransition {
+    fn default() -> Self {
+        Self::new(PanelTransition::default(), 0.25)
+    }
+}
+impl WorldSpaceUiTransition {
+    pub fn new(transition: PanelTransition, duration: f32) -> Self {
+        Self {
+            transition,
+            duration,
+            progress: 1.0,
+            base_scale: Vec3::ONE,
+        }
+    }
+
+    /// How open the surface is, from 0 to 1.
+    pub fn progress(&self) -> f32 {
+        self.progress
+    }
+
+    /// Starts from the current state and scale.
+    fn on_add(mut world: DeferredWorld, context: HookContext) {
+        let state = *world.get::<PanelState>(context.entity).unwrap();
+        let base_scale = world
+            .get::<Transform>(context.entity)
+            .map_or(Vec3::ONE, |transform| transform.scale);
+        let mut transition = world.get_mut::<Self>(context.entity).unwrap();
+        transition.base_scale = base_scale;
+        transition.progress = match state {
+            PanelState::Opening | PanelState::Closed => 0.0,
+            PanelState::Open | PanelState::Closing => 1.0,
+        };
+    }
+}
+
+/// Fades a `WorldSpaceUiSurface` out with dithering when the camera is closer than `near` or
+/// farther than `far`, e.g. so panels don't clip through the camera. Inserts a `VisibilityRange`.
+/// The surface stops receiving pointer input below `min_interactive_opacity`, measured at the
+/// distance of the hit, while the dithering is measured to the surface's origin.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component, Default)]
+#[component(on_insert = WorldSpaceUiDistanceFade::on_insert)]
+pub struct WorldSpaceUiDistanceFade {
+    /// Distances over which the surface fades in as the camera moves away.
+    pub near: Range<f32>,
+    /// Distances over which the surface fades out as the camera moves away.
+    pub far: Range<f32>,
+    pub min_interactive_opacity: f32,
+}
+impl Default for WorldSpaceUiDistanceFade {
+    fn default() -> Self {
+        Self {
+            near: 0.0..0.0,
+            far: 20.0..25.0,
+            min_interactive_opacity: 0.5,
+        }
+    }
+}
+impl WorldSpaceUiDistanceFade {
+    /// Opacity of the surface when viewed from `distance`.
+    pub fn opacity(&self, distance: f32) -> f32 {
+        let ramp = |range: &Range<f32>| {
+            if range.end > range.start {
+                ((distance - range.start) / (range.end - range.start)).clamp(0.0, 1.0)
+            } else if distance >= range.start {
+                1.0
+            } else {
+                0.0
+            }
+        };
+        ramp(&self.near) * (1.0 - ramp(&self.far))
+    }
+
+    /// On component insert, fade the surface with a matching `VisibilityRange`.
+    fn on_insert(mut world: DeferredWorld, context: HookContext) {
+        let fade = world.entity(context.entity).components::<&Self>().clone();
+        world
+            .commands()
+            .entity(context.entity)
+            .insert(VisibilityRange {
+                start_margin: fade.near,
+                end_margin: fade.far,
+                use_aabb: false,
+            });
+    }
+}
+
+/// How a `WorldSpaceUiSurface` maps hit UVs outside of [0, 1] onto the texture.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect)]
+#[reflect(Default)]
+pub enum UvMode {
+    /// Clamp to the texture edge.
+    #[default]
+    Clamp,
+    /// Repeat the texture, for tiled UVs.
+    Wrap,
+    /// Repeat the texture, mirroring every other tile.
+    Mirror,
+    /// Ignore hits outside of the texture.
+    Reject,
+}
+impl UvMode {
+    /// Maps a UV into [0, 1], or returns `None` if the hit should be ignored.
+    pub fn apply(self, uv: Vec2) -> Option<Vec2> {
+        match self {
+            Self::Clamp => Some(uv.clamp(Vec2::ZERO, Vec2::ONE)),
+            Self::Wrap => Some(uv.fract_gl()),
+            Self::Mirror => {
+                let t = uv.rem_euclid(Vec2::splat(2.0));
+                Some(Vec2::ONE - (t - Vec2::ONE).abs())
+            }
+            Self::Reject => (uv.cmpge(Vec2::ZERO).all() && uv.cmple(Vec2::ONE).all()).then_some(uv),
+        }
+    }
+}
+
+/// How a `WorldSpaceUiSurface` handles pointer hits on the back of its mesh.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect)]
+#[reflect(Default)]
+pub enum BackFaceMode {
+    /// Back faces are not raycast.
+    #[default]
+    Ignore,
+    /// Back hits use the mesh UVs, which appear mirrored from behind.
+    AsIs,
+    /// Back hits flip U, for two-sided signs whose back shows the UI readable.
+    FlipU,
+}
+
+/// Marks a mesh as a surface where UI will be rendered and interacted with.
+/// Add `UiSurfaceOf` to link the surface to its `WorldSpaceUiRoot`. Any number of surfaces
+/// can display one root, each with its own pointer.
+/// Add `WorldSpaceUiSurface2d` to display the UI on a sprite instead.
+/// When saving scenes, exclude the spawned virtual pointer since it is recreated on load.
+/// Generated pointer ids are marked with `GeneratedPointerId` and regenerated on load.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component, Default)]
+#[require(PreviousCursorPosition, HitWindow, LinkPending)]
+#[component(on_add = WorldSpaceUiSurface::on_add)]
+pub struct WorldSpaceUiSurface {
+    pub texture: Handle<Image>,
+    /// Virtual pointer driven by this surface.
+    /// If left as default, a unique id is generated when the surface is added, and the surface
+    /// gets `GeneratedPointerId`.
+    pub pointer_id: PointerId,
+    pub material: WorldSpaceUiMaterial,
+    pub material_settings: WorldSpaceUiMaterialSettings,
+    /// If the mesh has no UVs, project hits onto the plane of its AABB instead of ignoring them.
+    pub planar_uv_fallback: bool,
+    /// How hit UVs outside of [0, 1] are mapped onto the texture.
+    pub uv_mode: UvMode,
+    /// Moves the surface this far along its local +Z axis, the facing of quads like
+    /// `Rectangle`, when it is added, so panels mounted flush against walls don't z-fight with
+    /// them even where `depth_bias` isn't enough, e.g. 0.001.
+    pub normal_offset: f32,
+    /// Displays the whole texture instead of only the linked root's viewport, to composite
+    /// several roots rendering into viewports of one texture, e.g. gauges on a cockpit screen.
+    /// Link the surface to any of the roots. Pointer input goes to the root whose viewport
+    /// contains it.
+    pub full_texture: bool,
+    /// Region of the root's texture, or of its viewport, displayed and interacted with by this
+    /// surface, in UV coordinates. Use it to split one UI across several surfaces, e.g. the
+    /// panes of a physical console.
+    pub uv_rect: Rect,
+    /// How hits on the back of the mesh are handled. Set before the surface is added.
+    pub back_faces: BackFaceMode,
+    /// Simpler mesh raycast instead of the displayed one, e.g. a quad in front of a detailed
+    /// screen model. It is spawned as a `UiInteractionProxyOf` child when the surface is added.
+    pub interaction_mesh: Option<Handle<Mesh>>,
+    /// Mirrors the displayed UI horizontally, e.g. for reflections. Pointer hits are mirrored
+    /// to match. Only applies to owned materials.
+    pub flip_x: bool,
+    /// Mirrors the displayed UI vertically. Only applies to owned materials.
+    pub flip_y: bool,
+    /// Rays hitting the mesh at a larger angle from its normal, in radians, miss the surface,
+    /// to prevent accidental clicks on nearly edge-on screens.
+    pub max_incidence_angle: Option<f32>,
+}
+impl Default for WorldSpaceUiSurface {
+    fn default() -> Self {
+        Self {
+            texture: Handle::default(),
+            pointer_id: PointerId::default(),
+            material: WorldSpaceUiMaterial::default(),
+            material_settings: WorldSpaceUiMaterialSettings::default(),
+            planar_uv_fallback: false,
+            uv_mode: UvMode::default(),
+            normal_offset: 0.0,
+            full_texture: false,
+            uv_rect: Rect::new(0.0, 0.0, 1.0, 1.0),
+            back_faces: BackFaceMode::default(),
+            interaction_mesh: None,
+            flip_x: false,
+            flip_y: false,
+            max_incidence_angle: None,
+        }
+    }
+}
+impl WorldSpaceUiSurface {
+    /// On component add, attach a MeshMaterial3d using the image and spawn a custom pointer,
+    /// unless the surface is `WorldSpaceUiLazy`.
+    /// Linking to the root's render target is deferred to `link_pending_surfaces`.
+    fn on_add(mut world: DeferredWorld, context: HookContext) {
+        let entity = context.entity;
+        // Generated ids saved in scenes belong to the saved entity, so they are regenerated.
+        if world.get::<Self>(entity).unwrap().pointer_id == PointerId::default()
+            || world.entity(entity).contains::<GeneratedPointerId>()
+        {
+            world.get_mut::<Self>(entity).unwrap().pointer_id = Self::generate_pointer_id(entity);
+            world
+                .commands()
+                .entity(entity)
+                .insert_if_new(GeneratedPointerId);
+        }
+        if world.entity(entity).contains::<WorldSpaceUiLazy>() {
+            return;
+        }
+        Self::init(world, entity);
+    }
+
+    /// Attaches the material and spawns the virtual pointer.
+    fn init(mut world: DeferredWorld, entity: Entity) {
+        let surface = world.entity(entity).components::<&Self>().clone();
+
+        if surface.normal_offset != 0.0
+            && let Some(mut transform) = world.get_mut::<Transform>(entity)
+        {
+            let offset = transform.rotation * Vec3::Z * surface.normal_offset;
+            transform.translation += offset;
+        }
+
+        // This material has the texture that has been rendered.
+        // 2D surfaces display the texture on their sprite instead.
+        let is_2d = world.entity(entity).contains::<WorldSpaceUiSurface2d>();
+        let material_handle = match surface.material {
+            _ if is_2d => None,
+            WorldSpaceUiMaterial::Shared(handle) => Some(handle),
+            WorldSpaceUiMaterial::Custom => None,
+            owned => {
+                let material = owned
+                    .build(Some(surface.texture.clone()), &surface.material_settings)
+                    .unwrap();
+                Some(
+                    world
+                        .resource_mut::<Assets<StandardMaterial>>()
+                        .add(material),
+                )
+            }
+        };
+        if let Some(material_handle) = material_handle {
+            world
+                .commands()
+                .entity(entity)
+                .insert(MeshMaterial3d(material_handle));
+        }
+
+        if surface.back_faces != BackFaceMode::Ignore {
+            world.commands().entity(entity).insert(RayCastBackfaces);
+        }
+
+        if let Some(mesh) = surface.interaction_mesh {
+            world.commands().spawn((
+                Name::new("UiInteractionProxy"),
+                Mesh3d(mesh),
+                UiInteractionProxyOf(entity),
+                ChildOf(entity),
+            ));
+        }
+
+        // Spawn a virtual pointer so we can send events to the rendered UI.
+        world
+            .commands()
+            .spawn((Name::new("UiVirtualPointer"), surface.pointer_id));
+    }
+
+    /// Derives a pointer id unique to the surface entity.
+    fn generate_pointer_id(entity: Entity) -> PointerId {
+        const NAMESPACE: u64 = 0x7773_7569_5f70_7472; // "wsui_ptr"
+        PointerId::Custom(Uuid::from_u64_pair(NAMESPACE, entity.to_bits()))
+    }
+}
+
+/// Marks a `WorldSpaceUiSurface` whose `pointer_id` was generated. When the surface is loaded
+/// from a scene, its id is regenerated for the new entity instead of reusing the saved one,
+/// which may collide with the original surface. Remove it after setting a custom id.
+#[derive(Component, Debug, Clone, Default, Reflect)]
+#[reflect(Component, Default)]
+#[component(on_add = GeneratedPointerId::on_add)]
+pub struct GeneratedPointerId;
+impl GeneratedPointerId {
+    /// Regenerates the id of a surface added before this marker, e.g. by a scene, and moves its
+    /// virtual pointer to the new id.
+    fn on_add(mut world: DeferredWorld, context: HookContext) {
+        let entity = context.entity;
+        let generated = WorldSpaceUiSurface::generate_pointer_id(entity);
+        let Some(mut surface) = world.get_mut::<WorldSpaceUiSurface>(entity) else {
+            return;
+        };
+        let saved = core::mem::replace(&mut surface.pointer_id, generated);
+        if saved == generated || saved == PointerId::default() {
+            return;
+        }
+        world.commands().queue(move |world: &mut World| {
+            let mut pointers = world.query::<(Entity, &PointerId)>();
+            let stale: Vec<Entity> = pointers
+                .iter(world)
+                .filter(|(_, pointer_id)| **pointer_id == saved)
+                .map(|(pointer, _)| pointer)
+                .collect();
+            for pointer in stale {
+                world.entity_mut(pointer).insert(generated);
+            }
+        });
+    }
+}
+
+/// Shows a dot where a ray hits this surface, like a laser pointer, so players can see where
+/// their pointer lands on angled panels. Follows the surface's `WorldSpaceUiHit`.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component, Default)]
+#[component(on_add = WorldSpaceUiHitIndicator::on_add, on_remove = WorldSpaceUiHitIndicator::on_remove)]
+pub struct WorldSpaceUiHitIndicator {
+    /// Radius of the dot in world units.
+    pub radius: f32,
+    pub color: Color,
+    /// Distance the dot floats in front of the surface, to avoid z-fighting.
+    pub offset: f32,
+}
+impl Default for WorldSpaceUiHitIndicator {
+    fn default() -> Self {
+        Self {
+            radius: 0.01,
+            color: Color::srgb(1.0, 0.1, 0.1),
+            offset: 0.001,
+        }
+    }
+}
+impl WorldSpaceUiHitIndicator {
+    /// Spawns the hidden dot.
+    fn on_add(mut world: DeferredWorld, context: HookContext) {
+        let indicator = world.get::<Self>(context.entity).unwrap().clone();
+        let mesh = world
+            .resource_mut::<Assets<Mesh>>()
+            .add(Circle::new(indicator.radius));
+        let material = world
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(StandardMaterial {
+                base_color: indicator.color,
+                unlit: true,
+                ..default()
+            });
+        let dot = world
+            .commands()
+            .spawn((
+                Name::new("UiHitIndicator"),
+                Mesh3d(mesh),
+                MeshMaterial3d(material),
+                Visibility::Hidden,
+            ))
+            .id();
+        world
+            .commands()
+            .entity(context.entity)
+            .insert(WorldSpaceUiHitIndicatorEntity(dot));
+    }
+
+    /// Despawns the dot.
+    fn on_remove(mut world: DeferredWorld, context: HookContext) {
+        let Some(dot) = world
+            .get::<WorldSpaceUiHitIndicatorEntity>(context.entity)
+            .map(|dot| dot.0)
+        else {
+            return;
+        };
+        world
+            .commands()
+            .entity(context.entity)
+            .remove::<WorldSpaceUiHitIndicatorEntity>();
+        world.commands().entity(dot).try_despawn();
+    }
+}
+
+/// The dot spawned for a `WorldSpaceUiHitIndicator`.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct WorldSpaceUiHitIndicatorEntity(pub Entity);
+
+/// Draws a cursor image inside the UI texture of this root where a ray hits one of its
+/// surfaces, since the window's cursor doesn't match the panel's coordinate space.
+/// The image is a crate-managed `ImageNode` child of the root that ignores picking.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component, Default)]
+#[component(on_add = WorldSpaceUiCursorSprite::on_add, on_remove = WorldSpaceUiCursorSprite::on_remove)]
+pub struct WorldSpaceUiCursorSprite {
+    pub image: Handle<Image>,
+    /// Shown over nodes with `Interaction`, like buttons, instead of `image`.
+    pub hovered_image: Option<Handle<Image>>,
+    /// Shown while the primary button is pressed, instead of `image`.
+    pub pressed_image: Option<Handle<Image>>,
+    /// Size of the cursor in logical pixels.
+    pub size: Vec2,
+    /// Point of the image at the pointer position, in logical pixels from its top left.
+    pub hotspot: Vec2,
+}
+impl Default for WorldSpaceUiCursorSprite {
+    fn default() -> Self {
+        Self {
+            image: Handle::default(),
+            hovered_image: None,
+            pressed_image: None,
+            size: Vec2::splat(32.0),
+            hotspot: Vec2::ZERO,
+        }
+    }
+}
+impl WorldSpaceUiCursorSprite {
+    /// Spawns the hidden cursor node.
+    fn on_add(mut world: DeferredWorld, context: HookContext) {
+        let cursor = world.get::<Self>(context.entity).unwrap().clone();
+        let node = world
+            .commands()
+            .spawn((
+                Name::new("UiCursorSprite"),
+                ImageNode::new(cursor.image),
+                Node {
+                    position_type: PositionType::Absolute,
+                    width: Val::Px(cursor.size.x),
+                    height: Val::Px(cursor.size.y),
+                    ..default()
+                },
+                GlobalZIndex(i32::MAX),
+                Pickable::IGNORE,
+                Visibility::Hidden,
+                ChildOf(context.entity),
+            ))
+            .id();
+        world
+            .commands()
+            .entity(context.entity)
+            .insert(CursorSpriteNode(node));
+    }
+
+    /// Despawns the cursor node.
+    fn on_remove(mut world: DeferredWorld, context: HookContext) {
+        let Some(node) = world
+            .get::<CursorSpriteNode>(context.entity)
+            .map(|node| node.0)
+        else {
+            return;
+        };
+        world
+            .commands()
+            .entity(context.entity)
+            .remove::<CursorSpriteNode>();
+        world.commands().entity(node).try_despawn();
+    }
+}
+
+/// The node spawned for a `WorldSpaceUiCursorSprite`.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+struct CursorSpriteNode(Entity);
+
+/// Window cursor icon shown while a ray from the window's camera points at this node through a
+/// surface, e.g. a hand over buttons. On a `WorldSpaceUiSurface`, it applies to all nodes with
+/// `Interaction` that don't set their own. The window's icon is restored afterward.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct HoverCursorIcon(pub CursorIcon);
+
+/// Outlines this surface with a frame while it is hovered or has `WorldSpaceUiFocus`, so
+/// players can tell which screen has their input. The frame is spawned as a child around the
+/// face of the mesh's AABB, so it suits flat surfaces.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component, Default)]
+pub struct WorldSpaceUiHighlight {
+    pub color: Color,
+    /// Width of the frame in the mesh's local units.
+    pub width: f32,
+    /// Show the frame while a ray hits the surface.
+    pub on_hover: bool,
+    /// Show the frame while the surface has `WorldSpaceUiFocus`.
+    pub on_focus: bool,
+}
+impl Default for WorldSpaceUiHighlight {
+    fn default() -> Self {
+        Self {
+            color: Color::srgb(0.3, 0.7, 1.0),
+            width: 0.02,
+            on_hover: true,
+            on_focus: true,
+        }
+    }
+}
+
+/// The frame spawned for a `WorldSpaceUiHighlight`.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+struct HighlightFrame(Entity);
+
+/// Keeps a surface at `offset` from a moving `target`, e.g. above an NPC's head.
+/// The surface's `Transform` is overwritten, so it should not have a parent.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct AnchoredWorldSpaceUi {
+    #[entities]
+    pub target: Entity,
+    /// Offset from the target's position in world space.
+    pub offset: Vec3,
+    /// Turn the surface's front (+Z) toward the active 3D camera.
+    pub billboard: bool,
+    /// Rate at which the surface catches up with the target, or 0 to follow it exactly.
+    pub smoothing: f32,
+}
+impl AnchoredWorldSpaceUi {
+    /// Follows `target` exactly at `offset`, without turning.
+    pub fn new(target: Entity, offset: Vec3) -> Self {
+        Self {
+            target,
+            offset,
+            billboard: false,
+            smoothing: 0.0,
+        }
+    }
+}
+
+/// Creates a complete world space UI panel on this entity: the texture, a UI root,
+/// a quad mesh of `size`, and a `WorldSpaceUiSurface`.
+/// Spawn your UI as children of the root in `UiSurfaceOf`. It is despawned with the panel.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component, Default)]
+#[component(on_add = WorldSpaceUiPanel::on_add, on_remove = WorldSpaceUiPanel::on_remove)]
+pub struct WorldSpaceUiPanel {
+    /// Resolution of the UI texture in pixels.
+    pub resolution: UVec2,
+    /// Size of the quad in world units.
+    pub size: Vec2,
+    /// If set, overrides `size` with `resolution / pixels_per_meter`, so the quad matches the
+    /// UI's aspect ratio and text has the same physical size across panels.
+    pub pixels_per_meter: Option<f32>,
+    /// Settings for the created texture.
+    #[reflect(ignore)]
+    pub texture_settings: UiTextureSettings,
+    /// Supersampling multiplier, see `WorldSpaceUiRoot::supersample`.
+    pub supersample: f32,
+    /// Reuse a texture and camera from `UiTexturePool`, and return them to it on despawn.
+    pub pooled: bool,
+}
+impl Default for WorldSpaceUiPanel {
+    fn default() -> Self {
+        Self {
+            resolution: UVec2::splat(512),
+            size: Vec2::ONE,
+            pixels_per_meter: None,
+            texture_settings: UiTextureSettings::default(),
+            supersample: 1.0,
+            pooled: false,
+        }
+    }
+}
+impl WorldSpaceUiPanel {
+    /// A panel with a quad of `size` in world units displaying a UI of `resolution` pixels.
+    pub fn new(size: Vec2, resolution: UVec2) -> Self {
+        Self {
+            resolution,
+            size,
+            ..default()
+        }
+    }
+
+    /// Size of the quad in world units.
+    pub fn world_size(&self) -> Vec2 {
+        match self.pixels_per_meter {
+            Some(pixels_per_meter) => self.resolution.as_vec2() / pixels_per_meter,
+            None => self.size,
+        }
+    }
+
+    fn on_add(mut world: DeferredWorld, context: HookContext) {
+        let panel = world.entity(context.entity).components::<&Self>().clone();
+        let resolution = (panel.resolution.as_vec2() * panel.supersample)
+            .round()
+            .as_uvec2();
+        let pooled = panel
+            .pooled
+            .then(|| {
+                world
+                    .resource_mut::<UiTexturePool>()
+                    .take(resolution, panel.texture_settings.format)
+            })
+            .flatten();
+        let (texture, camera) = match pooled {
+            Some(target) => {
+                if let Some(mut camera) = world.get_mut::<Camera>(target.camera) {
+                    camera.is_active = true;
+                }
+                // Pooled textures may have been created with another sampler.
+                if let Some(image) = world
+                    .resource_mut::<Assets<Image>>()
+                    .get_mut(&target.texture)
+                {
+                    image.sampler = panel.texture_settings.sampler.clone();
+                }
+                world
+                    .commands()
+                    .entity(target.camera)
+                    .insert(RenderTarget::Image(ImageRenderTarget {
+                        handle: target.texture.clone(),
+                        scale_factor: panel.supersample,
+                    }));
+                (target.texture, Some(target.camera))
+            }
+            None => {
+                let texture = world.resource_mut::<Assets<Image>>().add(
+                    WorldSpaceUiRoot::get_ui_texture_with_settings(
+                        Extent3d {
+                            width: resolution.x,
+                            height: resolution.y,
+                            ..default()
+                        },
+                        &panel.texture_settings,
+                    ),
+                );
+                (texture, None)
+            }
+        };
+        let mesh = world
+            .resource_mut::<Assets<Mesh>>()
+            .add(Rectangle::from_size(panel.world_size()));
+        let root = world
+            .commands()
+            .spawn((
+                Name::new("WorldSpaceUiRoot"),
+                WorldSpaceUiRoot {
+                    texture: texture.clone(),
+                    camera,
+                    supersample: panel.supersample,
+                    ..default()
+                },
+                Node {
+                    // Cover the whole image
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    ..default()
+                },
+            ))
+            .id();
+        world.commands().entity(context.entity).insert((
+            Mesh3d(mesh),
+            WorldSpaceUiSurface {
+                texture,
+                ..default()
+            },
+            UiSurfaceOf(root),
+        ));
+    }
+
+    /// Despawns the UI root spawned in `on_add`, returning its texture and camera to the pool
+    /// if the panel is pooled.
+    fn on_remove(mut world: DeferredWorld, context: HookContext) {
+        let Some(root) = world
+            .get::<UiSurfaceOf>(context.entity)
+            .map(|surface_of| surface_of.0)
+        else {
+            return;
+        };
+        if world.get::<Self>(context.entity).unwrap().pooled
+            && let Some(ui_camera) = world.get::<UiTargetCamera>(root).map(|c| c.0)
+            && let Some(mut root_component) = world.get_mut::<WorldSpaceUiRoot>(root)
+        {
+            // Claim the camera so it survives the root's despawn.
+            root_component.camera = Some(ui_camera);
+            let texture = root_component.texture.clone();
+            if let Some(mut camera) = world.get_mut::<Camera>(ui_camera) {
+                camera.is_active = false;
+            }
+            if let Some(image) = world.resource::<Assets<Image>>().get(&texture) {
+                let key = (image.size(), image.texture_descriptor.format);
+                world
+                    .resource_mut::<UiTexturePool>()
+                    .free
+                    .entry(key)
+                    .or_default()
+                    .push(PooledUiTarget {
+                        texture,
+                        camera: ui_camera,
+                    });
+            }
+        }
+        world.commands().entity(root).try_despawn();
+    }
+}
+
+/// Entry point for spawning a world space UI in one expression:
+/// `WorldSpaceUi::build(resolution).with_surface_mesh(mesh).spawn(&mut commands)`.
+pub struct WorldSpaceUi;
+impl WorldSpaceUi {
+    /// Starts building a UI root rendering to a new texture of `resolution` pixels.
+    pub fn build(resolution: UVec2) -> WorldSpaceUiBuilder {
+        WorldSpaceUiBuilder {
+            resolution,
+            texture_settings: None,
+            mesh: None,
+            material: WorldSpaceUiMaterial::default(),
+            pointer_id: PointerId::default(),
+        }
+    }
+}
+
+/// Builds the texture, `WorldSpaceUiRoot`, and `WorldSpaceUiSurface` of a world space UI.
+/// See `WorldSpaceUi::build`.
+#[derive(Debug, Clone)]
+pub struct WorldSpaceUiBuilder {
+    resolution: UVec2,
+    texture_settings: Option<UiTextureSettings>,
+    mesh: Option<Handle<Mesh>>,
+    material: WorldSpaceUiMaterial,
+    pointer_id: PointerId,
+}
+impl WorldSpaceUiBuilder {
+    /// Mesh displaying the UI. Without one, insert a `Mesh3d` on the surface yourself.
+    pub fn with_surface_mesh(mut self, mesh: Handle<Mesh>) -> Self {
+        self.mesh = Some(mesh);
+        self
+    }
+
+    /// Material displaying the UI, see `WorldSpaceUiSurface::material`.
+    pub fn with_material(mut self, material: WorldSpaceUiMaterial) -> Self {
+        self.material = material;
+        self
+    }
+
+    /// Virtual pointer driven by the surface, see `WorldSpaceUiSurface::pointer_id`.
+    pub fn with_pointer(mut self, pointer_id: PointerId) -> Self {
+        self.pointer_id = pointer_id;
+        self
+    }
+
+    /// Settings for the created texture, instead of those in `WorldSpaceUiSettings`.
+    pub fn with_texture_settings(mut self, texture_settings: UiTextureSettings) -> Self {
+        self.texture_settings = Some(texture_settings);
+        self
+    }
+
+    /// Spawns the root and surface, returning `(root, surface)`.
+    /// Spawn your UI as children of the root, and position the surface with a `Transform`.
+    pub fn spawn(self, commands: &mut Commands) -> (Entity, Entity) {
+        let root = commands.spawn(Name::new("WorldSpaceUiRoot")).id();
+        let surface = commands.spawn(Name::new("WorldSpaceUiSurface")).id();
+        commands.queue(move |world: &mut World| {
+            let texture_settings = self.texture_settings.unwrap_or_else(|| {
+                world
+                    .resource::<WorldSpaceUiSettings>()
+                    .texture_settings
+                    .clone()
+            });
+            let texture = world.resource_mut::<Assets<Image>>().add(
+                WorldSpaceUiRoot::get_ui_texture_with_settings(
+                    Extent3d {
+                        width: self.resolution.x,
+                        height: self.resolution.y,
+                        ..default()
+                    },
+                    &texture_settings,
+                ),
+            );
+            world.entity_mut(root).insert((
+                WorldSpaceUiRoot {
+                    texture: texture.clone(),
+                    ..default()
+                },
+                Node {
+                    // Cover the whole image
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    ..default()
+                },
+            ));
+            let mut surface = world.entity_mut(surface);
+            if let Some(mesh) = self.mesh {
+                surface.insert(Mesh3d(mesh));
+            }
+            surface.insert((
+                WorldSpaceUiSurface {
+                    texture,
+                    pointer_id: self.pointer_id,
+                    material: self.material,
+                    ..default()
+                },
+                UiSurfaceOf(root),
+            ));
+        });
+        (root, surface)
+    }
+}
+
+/// Spawns world space UIs with sensible defaults straight from `Commands`.
+pub trait WorldSpaceUiCommands {
+    /// Pixels of UI texture per world unit used by `spawn_world_space_ui`.
+    const PIXELS_PER_METER: f32 = 512.0;
+
+    /// Spawns a UI root and a quad of `size` in world units displaying it, at
+    /// `PIXELS_PER_METER`, returning `(root, surface)`.
+    /// Spawn your UI as children of the root, and position the surface with a `Transform`.
+    fn spawn_world_space_ui(&mut self, size: Vec2) -> (Entity, Entity);
+}
+impl WorldSpaceUiCommands for Commands<'_, '_> {
+    fn spawn_world_space_ui(&mut self, size: Vec2) -> (Entity, Entity) {
+        let resolution = (size * Self::PIXELS_PER_METER)
+            .round()
+            .as_uvec2()
+            .max(UVec2::ONE);
+        let (root, surface) = WorldSpaceUi::build(resolution).spawn(self);
+        self.queue(move |world: &mut World| {
+            let mesh = world
+                .resource_mut::<Assets<Mesh>>()
+                .add(Rectangle::from_size(size));
+            world.entity_mut(surface).insert(Mesh3d(mesh));
+        });
+        (root, surface)
+    }
+}
+
+/// Recycles the textures and cameras of pooled `WorldSpaceUiPanel`s, so frequently created
+/// and destroyed panels don't churn GPU allocations.
+#[derive(Resource, Debug, Default)]
+pub struct UiTexturePool {
+    free: HashMap<(UVec2, TextureFormat), Vec<PooledUiTarget>>,
+}
+impl UiTexturePool {
+    /// Number of free textures in the pool.
+    pub fn len(&self) -> usize {
+        self.free.values().map(Vec::len).sum()
+    }
+
+    /// Returns true if the pool has no free textures.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Takes a free texture and camera of the given size and format, if any.
+    fn take(&mut self, size: UVec2, format: TextureFormat) -> Option<PooledUiTarget> {
+        self.free.get_mut(&(size, format))?.pop()
+    }
+}
+
+/// A texture and the inactive camera that renders to it, stored in `UiTexturePool`.
+#[derive(Debug)]
+struct PooledUiTarget {
+    texture: Handle<Image>,
+    camera: Entity,
+}
+
+/// Links pending surfaces to their root's render target once the root's UI camera exists
+/// and the texture has loaded. This lets surfaces and roots be spawned in any order.
+fn link_pending_surfaces(
+    mut commands: Commands,
+    surfaces: Query<
+        (
+            Entity,
+            &WorldSpaceUiSurface,
+            &UiSurfaceOf,
+            Option<&MeshMaterial3d<StandardMaterial>>,
+        ),
+        (With<LinkPending>, Without<WorldSpaceUiLazy>),
+    >,
+    roots: Query<(&WorldSpaceUiRoot, &UiTargetCamera)>,
+    cameras: Query<&RenderTarget>,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+    images: Res<Assets<Image>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for (entity, surface, surface_of, material) in surfaces.iter() {
+        let Ok((root, ui_camera)) = roots.get(surface_of.0) else {
+            continue;
+        };
+        let Ok(camera_target) = cameras.get(ui_camera.0) else {
+            continue;
+        };
+        let Some(target) = camera_target.normalize(primary_window.single().ok()) else {
+            warn_once!(
+                "World space UI surface {entity} has a UI camera with no valid render target"
+            );
+            continue;
+        };
+        let scale_factor = match camera_target {
+            RenderTarget::Image(image_target) => image_target.scale_factor,
+            _ => 1.0,
+        };
+        // The texture may still be loading, e.g. when spawned from a scene.
+        let Some(image) = images.get(&surface.texture) else {
+            continue;
+        };
+        let size = image.size();
+        let viewport = root.viewport.filter(|_| !surface.full_texture);
+
+        // Only display the surface's region of the root's texture.
+        if surface.material.is_owned()
+            && let Some(material) = material
+            && let Some(material) = materials.get_mut(&material.0)
+        {
+            let region = viewport.map_or(Rect::new(0.0, 0.0, 1.0, 1.0), |viewport| {
+                Rect::from_corners(
+                    viewport.min.as_vec2() / size.as_vec2(),
+                    viewport.max.as_vec2() / size.as_vec2(),
+                )
+            });
+            // Keep any UV transform of the template, e.g. a flip, within the region.
+            let template_transform = surface
+                .material
+                .build(None, &surface.material_settings)
+                .map_or(Affine2::IDENTITY, |template| template.uv_transform);
+            // Pointer hits follow the flip through the material's UV transform.
+            let flip = BVec2::new(surface.flip_x, surface.flip_y);
+            let flip_transform = Affine2::from_scale_angle_translation(
+                Vec2::select(flip, Vec2::NEG_ONE, Vec2::ONE),
+                0.0,
+                Vec2::select(flip, Vec2::ONE, Vec2::ZERO),
+            );
+            material.uv_transform = Affine2::from_scale_angle_translation(
+                region.size() * surface.uv_rect.size(),
+                0.0,
+                region.min + region.size() * surface.uv_rect.min,
+            ) * flip_transform
+                * template_transform;
+        }
+
+        commands
+            .entity(entity)
+            .remove::<LinkPending>()
+            .insert(WorldSpaceUiRenderTarget {
+                target,
+                size,
+                viewport,
+                scale_factor,
+                uv_rect: surface.uv_rect,
+            });
+    }
+}
+
+/// Sets up `WorldSpaceUiLazy` surfaces once visible, and lazy roots once any of their surfaces
+/// is visible.
+fn wake_lazy_world_space_ui(
+    mut commands: Commands,
+    surfaces: Query<(Entity, &ViewVisibility), (With<WorldSpaceUiSurface>, With<WorldSpaceUiLazy>)>,
+    roots: Query<(Entity, &UiSurfaces), (With<WorldSpaceUiRoot>, With<WorldSpaceUiLazy>)>,
+    visibility: Query<&ViewVisibility>,
+) {
+    for (entity, view_visibility) in surfaces.iter() {
+        if !view_visibility.get() {
+            continue;
+        }
+        commands.queue(move |world: &mut World| {
+            if world
+                .get_entity_mut(entity)
+                .is_ok_and(|mut entity| entity.take::<WorldSpaceUiLazy>().is_some())
+            {
+                WorldSpaceUiSurface::init(world.into(), entity);
+            }
+        });
+    }
+    for (entity, surfaces) in roots.iter() {
+        if !surfaces
+            .iter()
+            .any(|surface| visibility.get(surface).is_ok_and(|v| v.get()))
+        {
+            continue;
+        }
+        commands.queue(move |world: &mut World| {
+            let Ok(mut root) = world.get_entity_mut(entity) else {
+                return;
+            };
+            if root.take::<WorldSpaceUiLazy>().is_some() {
+                root.insert(Visibility::Inherited);
+                WorldSpaceUiRoot::init(world.into(), entity);
+            }
+        });
+    }
+}
+
+/// Spawns the cameras of `WorldSpaceUiScene3d`s, ordered before their root's UI camera.
+fn spawn_scene_3d_cameras(
+    mut commands: Commands,
+    roots: Query<
+        (
+            Entity,
+            &WorldSpaceUiRoot,
+            &WorldSpaceUiScene3d,
+            &UiTargetCamera,
+        ),
+        (Without<WorldSpaceUiScene3dCamera>, Without<ShareUiCamera>),
+    >,
+    mut cameras: Query<&mut Camera>,
+) {
+    for (entity, root, scene, ui_camera) in roots.iter() {
+        let Ok(mut camera) = cameras.get_mut(ui_camera.0) else {
+            continue;
+        };
+        // Clearing would wipe the scene rendered underneath.
+        camera.clear_color = ClearColorConfig::None;
+        let scene_camera = commands
+            .spawn((
+                Name::new("UiSceneCamera"),
+                Camera3d::default(),
+                Camera {
+                    order: camera.order - 1,
+                    clear_color: scene.clear_color.clone(),
+                    viewport: camera.viewport.clone(),
+                    ..default()
+                },
+                RenderTarget::Image(ImageRenderTarget {
+                    handle: root.texture.clone(),
+                    scale_factor: root.scale_factor * root.supersample,
+                }),
+                scene.transform,
+                scene.render_layers.clone(),
+            ))
+            .id();
+        commands
+            .entity(entity)
+            .insert(WorldSpaceUiScene3dCamera(scene_camera));
+    }
+}
+
+/// Gives spawned UI cameras that share a texture distinct orders whenever a root is added.
+fn dedup_ui_camera_orders(
+    added: Query<(), (Added<UiTargetCamera>, With<WorldSpaceUiRoot>)>,
+    roots: Query<(Entity, &WorldSpaceUiRoot, &UiTargetCamera), Without<ShareUiCamera>>,
+    mut cameras: Query<&mut Camera>,
+) {
+    if added.is_empty() {
+        return;
+    }
+    let mut by_texture = HashMap::<AssetId<Image>, Vec<(isize, Entity, Entity)>>::default();
+    for (entity, root, ui_camera) in roots.iter() {
+        if root.camera.is_none() {
+            by_texture.entry(root.texture.id()).or_default().push((
+                root.order,
+                entity,
+                ui_camera.0,
+            ));
+        }
+    }
+    for mut group in by_texture.into_values() {
+        dedup_orders(&mut group);
+        for (order, _, camera) in group {
+            if let Ok(mut camera) = cameras.get_mut(camera) {
+                camera.order = order;
+            }
+        }
+    }
+}
+
+/// Raises the `(order, root, camera)` orders of cameras sharing a texture until they strictly
+/// increase, keeping the requested order between them. Equal orders go by root entity.
+fn dedup_orders(group: &mut [(isize, Entity, Entity)]) {
+    group.sort();
+    let mut previous = None;
+    for (order, ..) in group.iter_mut() {
+        if let Some(previous) = previous {
+            *order = (*order).max(previous + 1);
+        }
+        previous = Some(*order);
+    }
+}
+
+/// Re-applies material settings to owned materials when a surface changes.
+fn sync_material_settings(
+    surfaces: Query<
+        (&WorldSpaceUiSurface, &MeshMaterial3d<StandardMaterial>),
+        Changed<WorldSpaceUiSurface>,
+    >,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for (surface, material) in surfaces.iter() {
+        if !surface.material.is_owned() {
+            continue;
+        }
+        if let Some(material) = materials.get_mut(&material.0) {
+            surface.material_settings.apply(material);
+        }
+    }
+}
+
+/// Advances `WorldSpaceUiFade` animations and tints their owned materials.
+fn animate_surface_fades(
+    mut surfaces: Query<(
+        Ref<WorldSpaceUiSurface>,
+        &mut WorldSpaceUiFade,
+        &MeshMaterial3d<StandardMaterial>,
+    )>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    time: Res<Time>,
+) {
+    for (surface, mut fade, material) in surfaces.iter_mut() {
+        // Settings changes rebuild the alpha mode, so reapply the final tint after them.
+        if fade.finished() && !fade.is_changed() && !surface.is_changed() {
+            continue;
+        }
+        fade.elapsed += time.delta_secs();
+        // Tint the colors the crate would build, so repeated fades don't compound.
+        let Some(original) = surface.material.build(None, &surface.material_settings) else {
+            continue;
+        };
+        let Some(material) = materials.get_mut(&material.0) else {
+            continue;
+        };
+        let tint = fade.tint();
+        material.base_color =
+            LinearRgba::from_vec4(original.base_color.to_linear().to_vec4() * tint.to_vec4())
+                .into();
+        material.emissive =
+            LinearRgba::from_vec4(original.emissive.to_vec4() * tint.to_vec4()) * tint.alpha;
+        material.alpha_mode = match original.alpha_mode {
+            AlphaMode::Opaque if tint.alpha < 1.0 => AlphaMode::Blend,
+            alpha_mode => alpha_mode,
+        };
+    }
+}
+
+/// Switches `WorldSpaceUiLod` surfaces by distance to the nearest window camera, and pauses
+/// roots whose surfaces all show their low texture.
+fn update_surface_lods(
+    mut commands: Commands,
+    surfaces: Query<(
+        Entity,
+        &WorldSpaceUiSurface,
+        &WorldSpaceUiLod,
+        &GlobalTransform,
+        Has<UiLodLow>,
+        Option<&MeshMaterial3d<StandardMaterial>>,
+        Option<&UiSurfaceOf>,
+    )>,
+    cameras: Query<(&Camera, &GlobalTransform, &RenderTarget)>,
+    roots: Query<(&UiSurfaces, Option<&UiLodPaused>)>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mut low_surfaces = HashMap::<Entity, bool>::default();
+    let mut lod_roots = HashSet::<Entity>::default();
+    for (entity, surface, lod, transform, was_low, material, surface_of) in surfaces.iter() {
+        let distance = cameras
+            .iter()
+            .filter(|(camera, _, target)| {
+                camera.is_active && matches!(target, RenderTarget::Window(_))
+            })
+            .map(|(_, camera_transform, _)| {
+                camera_transform
+                    .translation()
+                    .distance(transform.translation())
+            })
+            .fold(f32::INFINITY, f32::min);
+        let low = if was_low {
+            distance > lod.distance - lod.hysteresis
+        } else {
+            distance > lod.distance
+        };
+        low_surfaces.insert(entity, low);
+        if let Some(surface_of) = surface_of {
+            lod_roots.insert(surface_of.0);
+        }
+        if low == was_low {
+            continue;
+        }
+        if low {
+            commands.entity(entity).insert(UiLodLow);
+        } else {
+            commands.entity(entity).remove::<UiLodLow>();
+        }
+        if surface.material.is_owned()
+            && let Some(material) = material
+            && let Some(material) = materials.get_mut(&material.0)
+        {
+            let texture = if low {
+                lod.low_texture.clone()
+            } else {
+                surface.texture.clone()
+            };
+            if material.base_color_texture.is_some() {
+                material.base_color_texture = Some(texture.clone());
+            }
+            if material.emissive_texture.is_some() {
+                material.emissive_texture = Some(texture);
+            }
+        }
+    }
+
+    for root in lod_roots {
+        let Ok((root_surfaces, lod_paused)) = roots.get(root) else {
+            continue;
+        };
+        let paused = root_surfaces
+            .iter()
+            .all(|surface| low_surfaces.get(&surface).copied().unwrap_or(false));
+        if lod_paused.is_none_or(|lod_paused| lod_paused.0 != paused) {
+            commands.entity(root).insert(UiLodPaused(paused));
+        }
+    }
+}
+
+/// Updates `CurvedScreenMaterial`s when their `WorldSpaceUiCurvature` changes.
+fn sync_curvature(
+    surfaces: Query<
+        (
+            &WorldSpaceUiCurvature,
+            &MeshMaterial3d<CurvedScreenMaterial>,
+        ),
+        Changed<WorldSpaceUiCurvature>,
+    >,
+    mut materials: ResMut<Assets<CurvedScreenMaterial>>,
+) {
+    for (curvature, material) in surfaces.iter() {
+        if let Some(material) = materials.get_mut(&material.0) {
+            material.extension.curvature = curvature.0;
+        }
+    }
+}
+
+/// Checks for surfaces sharing a pointer id whenever any surface is added or changed.
+fn detect_pointer_id_collisions(
+    changed: Query<(), Changed<WorldSpaceUiSurface>>,
+    surfaces: Query<(Entity, &WorldSpaceUiSurface)>,
+    mut collisions: MessageWriter<PointerIdCollision>,
+) {
+    if changed.is_empty() {
+        return;
+    }
+    let mut owners = HashMap::<PointerId, Entity>::default();
+    for (entity, surface) in surfaces.iter() {
+        if let Some(&other) = owners.get(&surface.pointer_id) {
+            warn!(
+                "World space UI surfaces {other} and {entity} share pointer id {:?}",
+                surface.pointer_id
+            );
+            collisions.write(PointerIdCollision {
+                pointer_id: surface.pointer_id,
+                surfaces: [other, entity],
+            });
+        } else {
+            owners.insert(surface.pointer_id, entity);
+        }
+    }
+}
+
+/// Detaches linked surfaces that lost their `UiSurfaceOf`, e.g. because the root was despawned.
+/// Owned materials are rebuilt without the UI texture and the virtual pointer is despawned.
+/// Shared materials are left untouched since other surfaces may still use them.
+fn detach_orphaned_surfaces(
+    mut commands: Commands,
+    surfaces: Query<
+        (
+            Entity,
+            &WorldSpaceUiSurface,
+            Option<&MeshMaterial3d<StandardMaterial>>,
+        ),
+        (With<WorldSpaceUiRenderTarget>, Without<UiSurfaceOf>),
+    >,
+    pointers: Query<(Entity, &PointerId)>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for (entity, surface, material) in surfaces.iter() {
+        if let Some(reverted) = surface.material.build(None, &surface.material_settings)
+            && let Some(material) = material
+            && let Some(material) = materials.get_mut(&material.0)
+        {
+            *material = reverted;
+        }
+        for (pointer_entity, pointer_id) in pointers.iter() {
+            if *pointer_id == surface.pointer_id {
+                commands.entity(pointer_entity).despawn();
+            }
+        }
+        commands.entity(entity).remove::<WorldSpaceUiRenderTarget>();
+    }
+}
+
+/// Most candidate meshes one parallel raycast task casts against.
+const PARALLEL_RAYCAST_CHUNK: usize = 4;
+
+/// State of `drive_diegetic_pointer` kept across frames. The buffers are reused so steady
+/// state picking doesn't allocate.
+#[derive(Default)]
+struct DiegeticPointerState {
+    hovered: HashSet<(Entity, PointerId)>,
+    last_rays: HashMap<RayId, Ray3d>,
+    index: SurfaceIndex,
+    frame: u32,
+    settle_frames: u32,
+    warned_missing_uvs: HashSet<Entity>,
+    castable: HashSet<Entity>,
+    candidates: HashSet<Entity>,
+    ray_casts: Vec<RayCast>,
+    hits: HashSet<(Entity, PointerId)>,
+    nearest_hits: HashMap<Entity, WorldSpaceUiHit>,
+}
+
+/// Because bevy has no way to know how to map a mouse input to the UI texture, we need to write a
+/// system that tells it there is a pointer on the UI texture. We cast a ray into the scene and find
+/// the UV (2D texture) coordinates of the raycast hit. This UV coordinate is effectively the same
+/// as a pointer coordinate on a 2D UI rect.
+fn drive_diegetic_pointer(
+    mut commands: Commands,
+    mut raycast: MeshRayCast,
+    rays: Res<RayMap>,
+    settings: Res<WorldSpaceUiSettings>,
+    cameras: Query<&RenderTarget, With<Camera>>,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+    surfaces_check: Query<
+        (Has<UiInteractionProxies>, Option<&PanelState>),
+        (
+            With<WorldSpaceUiSurface>,
+            With<WorldSpaceUiRenderTarget>,
+            Without<UiLodLow>,
+        ),
+    >,
+    proxies: Query<&UiInteractionProxyOf>,
+    meshes: Query<
+        (
+            Entity,
+            Option<&UiInteractionProxyOf>,
+            (Option<&ViewVisibility>, Option<&InheritedVisibility>),
+            Option<&Aabb>,
+            &GlobalTransform,
+            Option<&Mesh3d>,
+            Has<RayCastBackfaces>,
+        ),
+        Or<(With<WorldSpaceUiSurface>, With<UiInteractionProxyOf>)>,
+    >,
+    mut surfaces: Query<(
+        &WorldSpaceUiSurface,
+        &WorldSpaceUiRenderTarget,
+        &mut PreviousCursorPosition,
+        &mut HitWindow,
+        &GlobalTransform,
+        Option<&Aabb>,
+        Option<&WorldSpaceUiCurvature>,
+        Option<&WorldSpaceUiDistanceFade>,
+        Option<&MeshMaterial3d<StandardMaterial>>,
+    )>,
+    (materials, mesh_assets, mut ray_meshes): (
+        Res<Assets<StandardMaterial>>,
+        Res<Assets<Mesh>>,
+        ResMut<RayMeshCache>,
+    ),
+    mut pointer_inputs: MessageWriter<PointerInput>,
+    (mut hover_starts, mut hover_ends): (
+        MessageWriter<WorldSpaceUiSurfaceHoverStart>,
+        MessageWriter<WorldSpaceUiSurfaceHoverEnd>,
+    ),
+    // Surfaces that moved, or started or stopped being castable, and edited materials,
+    // whose alpha decides which pixels are hit.
+    (moved, mut lod_restored, mut material_events): (
+        Query<
+            (),
+            (
+                Or<(With<WorldSpaceUiSurface>, With<UiInteractionProxyOf>)>,
+                Or<(
+                    Changed<GlobalTransform>,
+                    Changed<WorldSpaceUiSurface>,
+                    Changed<WorldSpaceUiRenderTarget>,
+                    Changed<PanelState>,
+                    Changed<Aabb>,
+                    Changed<ViewVisibility>,
+                    Changed<InheritedVisibility>,
+                    Added<UiLodLow>,
+                )>,
+            ),
+        >,
+        RemovedComponents<UiLodLow>,
+        MessageReader<AssetEvent<StandardMaterial>>,
+    ),
+    mut state: Local<DiegeticPointerState>,
+    mut diagnostics: Diagnostics,
+) -> Result {
+    let DiegeticPointerState {
+        hovered,
+        last_rays,
+        index,
+        frame,
+        settle_frames,
+        warned_missing_uvs,
+        castable,
+        candidates,
+        ray_casts,
+        hits,
+        nearest_hits,
+    } = &mut *state;
+    let lod_restored = lod_restored.read().count() > 0;
+    let moved = lod_restored || !moved.is_empty();
+    let modified: HashSet<AssetId<StandardMaterial>> = material_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    let materials_modified = !modified.is_empty()
+        && surfaces.iter().any(|(.., material)| {
+            material.is_some_and(|material| modified.contains(&material.id()))
+        });
+    // With idle pointers and static surfaces, the hits and pointer positions are unchanged.
+    let rays_unchanged = last_rays.len() == rays.iter().len()
+        && rays.iter().all(|(id, ray)| last_rays.get(id) == Some(ray));
+    // Keep casting until every background surface has been cast against the latest rays.
+    let interval = settings.background_raycast_interval.max(1);
+    if !rays_unchanged || moved || materials_modified || settings.is_changed() {
+        *settle_frames = interval;
+    }
+    if *settle_frames == 0 {
+        diagnostics.add_measurement(&WorldSpaceUiDiagnostics::RAYS_PROCESSED, || 0.0);
+        diagnostics.add_measurement(&WorldSpaceUiDiagnostics::SURFACES_RAYCAST, || 0.0);
+        return Ok(());
+    }
+    *settle_frames -= 1;
+    *frame = frame.wrapping_add(1);
+    last_rays.clear();
+    last_rays.extend(rays.iter().map(|(id, ray)| (*id, *ray)));
+    if moved {
+        index.rebuild(
+            meshes
+                .iter()
+                .map(|(entity, _, _, aabb, transform, ..)| (entity, aabb, transform)),
+        );
+    }
+
+    // Only cast against meshes of interactive surfaces, or their proxies, that can be hit.
+    // Hidden and off-screen surfaces are skipped before any casting.
+    hits.clear();
+    nearest_hits.clear();
+    castable.clear();
+    castable.extend(
+        meshes
+            .iter()
+            .filter(
+                |(entity, proxy_of, (view_visibility, inherited_visibility), ..)| {
+                    let surface = proxy_of.map_or(*entity, |proxy_of| proxy_of.0);
+                    let visible = match settings.ray_cast_visibility {
+                        RayCastVisibility::Any => true,
+                        RayCastVisibility::Visible => inherited_visibility.is_some_and(|v| v.get()),
+                        RayCastVisibility::VisibleInView => {
+                            view_visibility.is_some_and(|v| v.get())
+                        }
+                    };
+                    visible
+                        && surfaces_check
+                            .get(surface)
+                            .is_ok_and(|(has_proxies, state)| {
+                                (proxy_of.is_some() || !has_proxies)
+                                    && PanelState::interactive(state)
+                            })
+                },
+            )
+            .map(|(entity, ..)| entity),
+    );
+    let primary_window = primary_window.single().ok();
+    let mut surfaces_raycast = 0;
+
+    // Gather each ray's candidate meshes, reusing last frame's buffers.
+    ray_casts.truncate(rays.iter().len());
+    for (i, (id, ray)) in rays.iter().enumerate() {
+        if i == ray_casts.len() {
+            ray_casts.push(RayCast::new(*id, *ray));
+        }
+        let cast = &mut ray_casts[i];
+        (cast.id, cast.ray) = (*id, *ray);
+        cast.candidates.clear();
+        cast.hits.clear();
+        // Only cast against the few meshes near the ray.
+        candidates.clear();
+        index.query(*ray, candidates);
+        // Most rays miss most surfaces, so skip meshes whose bounds the ray misses.
+        // Distant surfaces that aren't hovered take turns being cast against.
+        candidates.retain(|entity| {
+            castable.contains(entity)
+                && meshes
+                    .get(*entity)
+                    .is_ok_and(|(_, proxy_of, _, aabb, transform, ..)| {
+                        let surface = proxy_of.map_or(*entity, |proxy_of| proxy_of.0);
+                        let foreground = interval == 1
+                            || transform.translation().distance(ray.origin)
+                                <= settings.foreground_distance
+                            || hovered.iter().any(|(hovered, _)| *hovered == surface);
+                        let turn = frame.wrapping_add(entity.to_bits() as u32) % interval == 0;
+                        (foreground || turn)
+                            && aabb.is_none_or(|aabb| ray_hits_bounds(*ray, aabb, transform))
+                    })
+        });
+        surfaces_raycast += candidates.len();
+        // Sorted, so hits at equal distances are ordered the same way every frame.
+        cast.candidates.extend(candidates.iter().copied());
+        cast.candidates.sort_unstable();
+    }
+
+    // Read the vertex data of new meshes once, so hits only look up cached UVs.
+    for entity in ray_casts.iter().flat_map(|cast| &cast.candidates) {
+        if let Ok((.., Some(mesh), _)) = meshes.get(*entity) {
+            ray_meshes.cache(mesh.0.id(), &mesh_assets);
+        }
+    }
+
+    // Find raycast hits.
+    if settings.parallel_raycast {
+        // Each task casts one ray against a few of its candidates. The scope returns their
+        // hits in spawn order, so merging them doesn't depend on which task finishes first.
+        let (meshes, ray_meshes) = (&meshes, &*ray_meshes);
+        let task_hits = ComputeTaskPool::get().scope(|scope| {
+            for (i, cast) in ray_casts.iter().enumerate() {
+                for chunk in cast.candidates.chunks(PARALLEL_RAYCAST_CHUNK) {
+                    let ray = cast.ray;
+                    scope.spawn(async move {
+                        let hits = chunk
+                            .iter()
+                            .filter_map(|entity| {
+                                let (.., transform, mesh, backfaces) = meshes.get(*entity).ok()?;
+                                let mesh = ray_meshes.get(mesh?.0.id())?;
+                                let hit = ray_mesh_hit(ray, mesh, transform, backfaces)?;
+                                Some((*entity, hit))
+                            })
+                            .collect::<Vec<_>>();
+                        (i, hits)
+                    });
+                }
+            }
+        });
+        for (i, hits) in task_hits {
+            ray_casts[i].hits.extend(hits);
+        }
+        for cast in ray_casts.iter_mut() {
+            cast.hits
+                .sort_by(|(_, a), (_, b)| a.distance.total_cmp(&b.distance));
+            if settings.nearest_hit_only {
+                cast.hits.truncate(1);
+            }
+        }
+    } else {
+        for cast in ray_casts.iter_mut() {
+            if cast.candidates.is_empty() {
+                continue;
+            }
+            let raycast_settings = MeshRayCastSettings {
+                visibility: settings.ray_cast_visibility,
+                filter: &|entity| cast.candidates.binary_search(&entity).is_ok(),
+                early_exit_test: &|_| settings.nearest_hit_only,
+            };
+            let hits = raycast.cast_ray(cast.ray, &raycast_settings);
+            cast.hits.extend(hits.iter().map(|(entity, hit)| {
+                let uv = meshes
+                    .get(*entity)
+                    .ok()
+                    .and_then(|(.., mesh, _)| ray_meshes.get(mesh?.0.id()))
+                    .zip(hit.triangle_index)
+                    .and_then(|(mesh, triangle)| mesh.uv(triangle, hit.barycentric_coords));
+                (
+                    *entity,
+                    MeshHit {
+                        point: hit.point,
+                        normal: hit.normal,
+                        uv,
+                        distance: hit.distance,
+                    },
+                )
+            }));
+        }
+    }
+
+    // Update the virtual pointers, in the order the rays were gathered.
+    for RayCast {
+        id,
+        ray,
+        hits: ray_hits,
+        ..
+    } in ray_casts.iter()
+    {
+        // Rays may come from cameras on any window, so remember which one hit each surface.
+        let window = cameras
+            .get(id.camera)
+            .ok()
+            .and_then(|target| target.normalize(primary_window))
+            .and_then(|target| match target {
+                NormalizedRenderTarget::Window(window) => Some(window.entity()),
+                _ => None,
+            });
+        for (hit_entity, hit) in ray_hits {
+            let cube = proxies
+                .get(*hit_entity)
+                .map_or(*hit_entity, |proxy_of| proxy_of.0);
+            let (
+                surface,
+                render_target,
+                mut cursor_last,
+                mut hit_window,
+                transform,
+                aabb,
+                curvature,
+                distance_fade,
+                material,
+            ) = surfaces.get_mut(cube)?;
+            // Measure to the hit itself, since large or scaled panels extend far from their origin.
+            let interactive = match distance_fade {
+                Some(fade) => fade.opacity(hit.distance) >= fade.min_interactive_opacity,
+                None => hit.distance <= settings.max_interaction_distance,
+            };
+            if !interactive {
+                continue;
+            }
+            let uv = match hit.uv {
+                Some(uv) => uv,
+                None => {
+                    if warned_missing_uvs.insert(cube) {
+                        warn!("World space UI surface {cube} has a mesh without UVs");
+                    }
+                    match aabb {
+                        Some(aabb) if surface.planar_uv_fallback => {
+                            planar_uv(hit.point, transform, aabb)
+                        }
+                        _ => continue,
+                    }
+                }
+            };
+            let facing = ray.direction.dot(hit.normal);
+            if let Some(max_angle) = surface.max_incidence_angle
+                && facing.abs() < max_angle.cos()
+            {
+                continue;
+            }
+            let is_back_face = facing > 0.0;
+            let uv = match surface.back_faces {
+                BackFaceMode::FlipU if is_back_face => Vec2::new(1.0 - uv.x, uv.y),
+                _ => uv,
+            };
+            let material = material.and_then(|material| materials.get(&material.0));
+            let Some(position) = hit_position(surface, render_target, uv, curvature, material)
+            else {
+                continue;
+            };
+            if hits.insert((cube, id.pointer)) && !hovered.contains(&(cube, id.pointer)) {
+                hover_starts.write(WorldSpaceUiSurfaceHoverStart {
+                    surface: cube,
+                    pointer: id.pointer,
+                    uv,
+                    world_position: hit.point,
+                });
+            }
+            if nearest_hits
+                .get(&cube)
+                .is_none_or(|nearest| hit.distance < nearest.distance)
+            {
+                nearest_hits.insert(
+                    cube,
+                    WorldSpaceUiHit {
+                        pointer: id.pointer,
+                        uv,
+                        position,
+                        world_position: hit.point,
+                        normal: hit.normal,
+                        distance: hit.distance,
+                    },
+                );
+            }
+            hit_window.0 = window;
+            write_pointer_move(
+                &mut pointer_inputs,
+                surface.pointer_id,
+                render_target,
+                &mut cursor_last,
+                position,
+            );
+        }
+    }
+
+    for &(surface, pointer) in hovered.difference(hits) {
+        hover_ends.write(WorldSpaceUiSurfaceHoverEnd { surface, pointer });
+        // Window input stops going to surfaces no ray hits anymore.
+        if !nearest_hits.contains_key(&surface) {
+            commands.entity(surface).try_remove::<WorldSpaceUiHit>();
+            if let Ok((_, _, _, mut hit_window, ..)) = surfaces.get_mut(surface) {
+                hit_window.0 = None;
+            }
+        }
+    }
+    for (surface, hit) in nearest_hits.drain() {
+        commands.entity(surface).insert(hit);
+    }
+    core::mem::swap(hovered, hits);
+    diagnostics.add_measurement(&WorldSpaceUiDiagnostics::RAYS_PROCESSED, || {
+        rays.iter().len() as f64
+    });
+    diagnostics.add_measurement(&WorldSpaceUiDiagnostics::SURFACES_RAYCAST, || {
+        surfaces_raycast as f64
+    });
+
+    Ok(())
+}
+
+/// Moves surface pointers from `WorldSpaceUiRayHit`s, e.g. from physics raycasts.
+fn apply_external_ray_hits(
+    mut commands: Commands,
+    mut hits: MessageReader<WorldSpaceUiRayHit>,
+    mut surfaces: Query<(
+        &WorldSpaceUiSurface,
+        &WorldSpaceUiRenderTarget,
+        &mut PreviousCursorPosition,
+        &mut HitWindow,
+        &GlobalTransform,
+        Option<&Aabb>,
+        Option<&WorldSpaceUiCurvature>,
+        Option<&MeshMaterial3d<StandardMaterial>>,
+        Option<&PanelState>,
+    )>,
+    materials: Res<Assets<StandardMaterial>>,
+    mut pointer_inputs: MessageWriter<PointerInput>,
+    mut hit_surfaces: Local<HashSet<Entity>>,
+    mut nearest_hits: Local<HashMap<Entity, WorldSpaceUiHit>>,
+) {
+    for hit in hits.read() {
+        let Ok((
+            surface,
+            render_target,
+            mut cursor_last,
+            mut hit_window,
+            transform,
+            aabb,
+            curvature,
+            material,
+            state,
+        )) = surfaces.get_mut(hit.surface)
+        else {
+            continue;
+        };
+        if !PanelState::interactive(state) {
+            continue;
+        }
+        let Some(uv) = hit
+            .uv
+            .or_else(|| aabb.map(|aabb| planar_uv(hit.point, transform, aabb)))
+        else {
+            continue;
+        };
+        let material = material.and_then(|material| materials.get(&material.0));
+        let Some(position) = hit_position(surface, render_target, uv, curvature, material) else {
+            continue;
+        };
+        if nearest_hits
+            .get(&hit.surface)
+            .is_none_or(|nearest| hit.distance < nearest.distance)
+        {
+            nearest_hits.insert(
+                hit.surface,
+                WorldSpaceUiHit {
+                    pointer: hit.pointer,
+                    uv,
+                    position,
+                    world_position: hit.point,
+                    normal: hit.normal,
+                    distance: hit.distance,
+                },
+            );
+        }
+        hit_window.0 = hit.window;
+        write_pointer_move(
+            &mut pointer_inputs,
+            surface.pointer_id,
+            render_target,
+            &mut cursor_last,
+            position,
+        );
+    }
+
+    // Window input stops going to surfaces the backend no longer hits.
+    for &surface in hit_surfaces.iter() {
+        if nearest_hits.contains_key(&surface) {
+            continue;
+        }
+        commands.entity(surface).try_remove::<WorldSpaceUiHit>();
+        if let Ok((_, _, _, mut hit_window, ..)) = surfaces.get_mut(surface) {
+            hit_window.0 = None;
+        }
+    }
+    hit_surfaces.clear();
+    for (surface, hit) in nearest_hits.drain() {
+        hit_surfaces.insert(surface);
+        commands.entity(surface).insert(hit);
+    }
+}
+
+/// Converts a hit UV on a surface's mesh to a pointer position on its render target,
+/// or `None` if nothing is displayed there.
+fn hit_position(
+    surface: &WorldSpaceUiSurface,
+    render_target: &WorldSpaceUiRenderTarget,
+    uv: Vec2,
+    curvature: Option<&WorldSpaceUiCurvature>,
+    material: Option<&StandardMaterial>,
+) -> Option<Vec2> {
+    let uv = surface.uv_mode.apply(uv)?;
+    // Nothing is displayed outside of a curved screen.
+    let uv = match curvature {
+        Some(curvature) => curvature.apply(uv),
+        None => uv,
+    };
+    if !(uv.cmpge(Vec2::ZERO).all() && uv.cmple(Vec2::ONE).all()) {
+        return None;
+    }
+    // Follow the material's UV transform, e.g. when it scrolls or flips the texture.
+    Some(match material {
+        Some(material) => {
+            render_target.texture_position(material.uv_transform.transform_point2(uv))
+        }
+        None => render_target.position(uv),
+    })
+}
+
+/// Moves a surface's virtual pointer to `position` if it changed.
+fn write_pointer_move(
+    pointer_inputs: &mut MessageWriter<PointerInput>,
+    pointer_id: PointerId,
+    render_target: &WorldSpaceUiRenderTarget,
+    cursor_last: &mut PreviousCursorPosition,
+    position: Vec2,
+) {
+    if position == cursor_last.0 {
+        return;
+    }
+    pointer_inputs.write(PointerInput::new(
+        pointer_id,
+        Location {
+            target: render_target.target.clone(),
+            position,
+        },
+        PointerAction::Move {
+            delta: position - cursor_last.0,
+        },
+    ));
+    cursor_last.0 = position;
+}
+
+/// Sends `InjectPointerInput` requests to the targeted surfaces' virtual pointers.
+fn inject_pointer_input(
+    mut injections: MessageReader<InjectPointerInput>,
+    mut surfaces: Query<(
+        &WorldSpaceUiSurface,
+        &WorldSpaceUiRenderTarget,
+        &mut PreviousCursorPosition,
+    )>,
+    mut pointer_inputs: MessageWriter<PointerInput>,
+) {
+    for injection in injections.read() {
+        // Surfaces that aren't linked to their root's texture yet have no render target.
+        let Ok((surface, render_target, mut cursor_last)) = surfaces.get_mut(injection.surface)
+        else {
+            warn!(
+                "Dropped injected pointer input for {}, which is not a ready world space UI surface",
+                injection.surface
+            );
+            continue;
+        };
+        let position = render_target.position(injection.uv);
+        write_pointer_move(
+            &mut pointer_inputs,
+            surface.pointer_id,
+            render_target,
+            &mut cursor_last,
+            position,
+        );
+        if !matches!(injection.action, PointerAction::Move { .. }) {
+            pointer_inputs.write(PointerInput::new(
+                surface.pointer_id,
+                Location {
+                    target: render_target.target.clone(),
+                    position,
+                },
+                injection.action,
+            ));
+        }
+    }
+}
+
+/// Computes a UV for a world space point by projecting it onto the plane of the mesh AABB,
+/// using the AABB's thinnest axis as the plane normal.
+fn planar_uv(point: Vec3, transform: &GlobalTransform, aabb: &Aabb) -> Vec2 {
+    let local = Vec3A::from(transform.affine().inverse().transform_point3(point));
+    let size = (aabb.max() - aabb.min()).max(Vec3A::splat(f32::EPSILON));
+    let uvw = (local - aabb.min()) / size;
+    let extents = aabb.half_extents;
+    if extents.z <= extents.x && extents.z <= extents.y {
+        // Facing Z, like `Rectangle`.
+        Vec2::new(uvw.x, 1.0 - uvw.y)
+    } else if extents.y <= extents.x {
+        // Facing Y, like `Plane3d`.
+        Vec2::new(uvw.x, uvw.z)
+    } else {
+        // Facing X.
+        Vec2::new(uvw.z, 1.0 - uvw.y)
+    }
+}
+
+/// Computes the world space point with a UV on the plane of the mesh AABB, through its center.
+/// Inverse of `planar_uv`.
+fn planar_point(uv: Vec2, transform: &GlobalTransform, aabb: &Aabb) -> Vec3 {
+    let extents = aabb.half_extents;
+    let uvw = if extents.z <= extents.x && extents.z <= extents.y {
+        Vec3A::new(uv.x, 1.0 - uv.y, 0.5)
+    } else if extents.y <= extents.x {
+        Vec3A::new(uv.x, 0.5, uv.y)
+    } else {
+        Vec3A::new(0.5, 1.0 - uv.y, uv.x)
+    };
+    let local = aabb.min() + uvw * (aabb.max() - aabb.min());
+    transform.affine().transform_point3a(local).into()
+}
+
+/// Send pointer pressed and released events to the world space UI.
+/// Input is only forwarded to surfaces last hit from the window that received it, except that
+/// releases always reach the surface in `WorldSpaceUiPresses`, even after the ray leaves it,
+/// so presses can't get stuck. Surfaces hit by a ray also get `WorldSpaceUiSurfacePress` and `WorldSpaceUiSurfaceRelease`.
 fn send_pointer_input(
+    mut commands: Commands,
+    mut presses: ResMut<WorldSpaceUiPresses>,
     surfaces: Query<(
+        Entity,
         &WorldSpaceUiSurface,
         &WorldSpaceUiRenderTarget,
         &PreviousCursorPosition,
+        &HitWindow,
+        Option<&WorldSpaceUiHit>,
+        Option<&PanelState>,
     )>,
     mut window_events: MessageReader<WindowEvent>,
     mut pointer_input: MessageWriter<PointerInput>,
+    mut inputs: Local<Vec<PointerInput>>,
+    mut held: Local<HashMap<(PointerId, PointerButton), Vec<Entity>>>,
 ) {
     // Pipe pointer button presses to the virtual pointer on the UI texture.
     for window_event in window_events.read() {
@@ -241,8 +3408,59 @@ fn send_pointer_input(
                 ButtonState::Pressed => PointerAction::Press(button),
                 ButtonState::Released => PointerAction::Release(button),
             };
-            for (surface, render_target, cursor_last) in surfaces.iter() {
-                pointer_input.write(PointerInput::new(
+            let key = (PointerId::Mouse, button);
+            // Every surface receiving a press receives its release, even once no ray hits it.
+            // Surfaces without a `WorldSpaceUiHit`, like 2D surfaces, are pressed from their
+            // `HitWindow` alone.
+            let pressed = match input.state {
+                ButtonState::Pressed => {
+                    let pressed: Vec<(Entity, f32)> = surfaces
+                        .iter()
+                        .filter(|(.., hit_window, _, state)| {
+                            hit_window.0 == Some(input.window) && PanelState::interactive(*state)
+                        })
+                        .map(|(entity, .., hit, _)| {
+                            (entity, hit.map_or(f32::INFINITY, |hit| hit.distance))
+                        })
+                        .collect();
+                    let nearest = pressed.iter().min_by(|(_, a), (_, b)| a.total_cmp(b));
+                    if let Some(&(entity, _)) = nearest {
+                        presses.0.insert(key, entity);
+                    }
+                    held.insert(key, pressed.into_iter().map(|(entity, _)| entity).collect());
+                    Vec::new()
+                }
+                ButtonState::Released => {
+                    presses.0.remove(&key);
+                    held.remove(&key).unwrap_or_default()
+                }
+            };
+            for (entity, surface, render_target, cursor_last, hit_window, hit, state) in
+                surfaces.iter()
+            {
+                if hit_window.0 != Some(input.window) && !pressed.contains(&entity) {
+                    continue;
+                }
+                // Releases still go through so presses can't get stuck.
+                if input.state == ButtonState::Pressed && !PanelState::interactive(state) {
+                    continue;
+                }
+                if let Some(hit) = hit {
+                    let hit = hit.clone();
+                    match input.state {
+                        ButtonState::Pressed => commands.trigger(WorldSpaceUiSurfacePress {
+                            entity,
+                            button,
+                            hit,
+                        }),
+                        ButtonState::Released => commands.trigger(WorldSpaceUiSurfaceRelease {
+                            entity,
+                            button,
+                            hit,
+                        }),
+                    }
+                }
+                inputs.push(PointerInput::new(
                     surface.pointer_id,
                     Location {
                         target: render_target.target.clone(),
@@ -253,4 +3471,978 @@ fn send_pointer_input(
             }
         }
     }
+    pointer_input.write_batch(inputs.drain(..));
+}
+
+/// Moves `WorldSpaceUiFocus` to a pressed surface.
+fn focus_pressed_surface(
+    press: On<WorldSpaceUiSurfacePress>,
+    mut focus: ResMut<WorldSpaceUiFocus>,
+    mut gained: MessageWriter<WorldSpaceUiFocusGained>,
+    mut lost: MessageWriter<WorldSpaceUiFocusLost>,
+) {
+    let surface = press.entity;
+    if focus.0 == Some(surface) {
+        return;
+    }
+    if let Some(previous) = focus.0.replace(surface) {
+        lost.write(WorldSpaceUiFocusLost { surface: previous });
+    }
+    gained.write(WorldSpaceUiFocusGained { surface });
+}
+
+/// Tags inputs to the virtual pointers of surfaces with `RecordPointerEvents`.
+fn record_pointer_events(
+    mut pointer_inputs: MessageReader<PointerInput>,
+    surfaces: Query<
+        (Entity, &WorldSpaceUiSurface, &WorldSpaceUiRenderTarget),
+        With<RecordPointerEvents>,
+    >,
+    mut events: MessageWriter<WorldSpaceUiPointerEvent>,
+) {
+    if surfaces.is_empty() {
+        pointer_inputs.clear();
+        return;
+    }
+    for input in pointer_inputs.read() {
+        for (entity, surface, render_target) in surfaces.iter() {
+            if surface.pointer_id == input.pointer_id {
+                events.write(WorldSpaceUiPointerEvent {
+                    surface: entity,
+                    uv: render_target.texture_uv(input.location.position),
+                    action: input.action,
+                });
+            }
+        }
+    }
+}
+
+/// Reallocates UI textures in response to `ResizeWorldSpaceUi`.
+/// Dependent surfaces are updated by `refresh_modified_textures`.
+fn resize_world_space_ui(
+    mut resizes: MessageReader<ResizeWorldSpaceUi>,
+    roots: Query<&WorldSpaceUiRoot>,
+    mut images: ResMut<Assets<Image>>,
+    mut resized: MessageWriter<WorldSpaceUiResized>,
+) {
+    for resize in resizes.read() {
+        let Ok(root) = roots.get(resize.root) else {
+            continue;
+        };
+        let Some(image) = images.get_mut(&root.texture) else {
+            continue;
+        };
+        let old = image.size();
+        if old == resize.size {
+            continue;
+        }
+        image.resize(Extent3d {
+            width: resize.size.x,
+            height: resize.size.y,
+            depth_or_array_layers: 1,
+        });
+        if image.texture_descriptor.mip_level_count > 1 {
+            image.texture_descriptor.mip_level_count = mipmaps::mip_level_count(resize.size);
+        }
+        resized.write(WorldSpaceUiResized {
+            root: resize.root,
+            old,
+            new: resize.size,
+        });
+    }
+}
+
+/// Keeps cameras, materials, and surfaces in sync when a UI texture is modified or reloaded.
+fn refresh_modified_textures(
+    mut image_events: MessageReader<AssetEvent<Image>>,
+    roots: Query<(&WorldSpaceUiRoot, &UiTargetCamera)>,
+    mut camera_targets: Query<&mut RenderTarget>,
+    mut surfaces: Query<(
+        &WorldSpaceUiSurface,
+        Option<&MeshMaterial3d<StandardMaterial>>,
+        &mut WorldSpaceUiRenderTarget,
+        &mut PreviousCursorPosition,
+    )>,
+    images: Res<Assets<Image>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let modified: HashSet<AssetId<Image>> = image_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } | AssetEvent::LoadedWithDependencies { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    if modified.is_empty() {
+        return;
+    }
+
+    for (root, ui_camera) in roots.iter() {
+        if modified.contains(&root.texture.id())
+            && let Ok(mut target) = camera_targets.get_mut(ui_camera.0)
+        {
+            target.set_changed();
+        }
+    }
+
+    for (surface, material, mut render_target, mut cursor_last) in surfaces.iter_mut() {
+        if !modified.contains(&surface.texture.id()) {
+            continue;
+        }
+        let Some(image) = images.get(&surface.texture) else {
+            continue;
+        };
+        let size = image.size();
+        if size != render_target.size {
+            cursor_last.0 = cursor_last.0 * size.as_vec2() / render_target.size.as_vec2();
+            render_target.size = size;
+        }
+        // Touch the material so its bind group is rebuilt against the new texture.
+        if let Some(material) = material {
+            materials.get_mut(&material.0);
+        }
+    }
+}
+
+/// Moves `AnchoredWorldSpaceUi` surfaces to their targets.
+fn follow_anchors(
+    mut anchored: Query<(&AnchoredWorldSpaceUi, &mut Transform)>,
+    targets: Query<&GlobalTransform>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    time: Res<Time>,
+) {
+    let camera = cameras
+        .iter()
+        .filter(|(camera, _)| camera.is_active)
+        .max_by_key(|(camera, _)| camera.order)
+        .map(|(_, transform)| transform.translation());
+    for (anchor, mut transform) in anchored.iter_mut() {
+        let Ok(target) = targets.get(anchor.target) else {
+            continue;
+        };
+        let goal = target.translation() + anchor.offset;
+        transform.translation = if anchor.smoothing > 0.0 {
+            let t = 1.0 - (-anchor.smoothing * time.delta_secs()).exp();
+            transform.translation.lerp(goal, t)
+        } else {
+            goal
+        };
+        if anchor.billboard
+            && let Some(camera) = camera
+        {
+            let direction = transform.translation - camera;
+            if direction != Vec3::ZERO {
+                transform.look_to(direction, Vec3::Y);
+            }
+        }
+    }
+}
+
+/// Advances `WorldSpaceUiTransition`s toward their `PanelState`, finishing `Opening` and
+/// `Closing` once done.
+fn animate_panel_transitions(
+    mut commands: Commands,
+    mut surfaces: Query<(
+        Entity,
+        &mut WorldSpaceUiTransition,
+        Ref<PanelState>,
+        &mut Transform,
+        Option<&mut Visibility>,
+    )>,
+    time: Res<Time>,
+) {
+    for (entity, mut transition, state, mut transform, visibility) in surfaces.iter_mut() {
+        let target = match *state {
+            PanelState::Opening | PanelState::Open => 1.0,
+            PanelState::Closing | PanelState::Closed => 0.0,
+        };
+        if transition.progress == target && !state.is_changed() {
+            continue;
+        }
+        let remaining = (target - transition.progress).abs() * transition.duration;
+        if state.is_changed() && transition.transition == PanelTransition::Dissolve {
+            commands.entity(entity).insert(WorldSpaceUiFade {
+                from: Color::WHITE.with_alpha(transition.progress),
+                to: Color::WHITE.with_alpha(target),
+                duration: remaining,
+                elapsed: 0.0,
+            });
+        }
+
+        let step = if transition.duration > 0.0 {
+            time.delta_secs() / transition.duration
+        } else {
+            1.0
+        };
+        let progress = transition.progress;
+        transition.progress = progress + (target - progress).clamp(-step, step);
+        if transition.progress == target {
+            let finished = match *state {
+                PanelState::Opening => Some(PanelState::Open),
+                PanelState::Closing => Some(PanelState::Closed),
+                _ => None,
+            };
+            if let Some(finished) = finished {
+                commands.entity(entity).insert(finished);
+            }
+        }
+
+        // Zero scales can't be inverted to map pointer hits.
+        let progress = transition.progress.max(1e-4);
+        transform.scale = match transition.transition {
+            PanelTransition::Scale => transition.base_scale * progress,
+            PanelTransition::Wipe => transition.base_scale * Vec3::new(1.0, progress, 1.0),
+            PanelTransition::Dissolve => transition.base_scale,
+        };
+        if let Some(mut visibility) = visibility {
+            visibility.set_if_neq(if transition.progress > 0.0 {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            });
+        }
+    }
+}
+
+/// Spawns frames for `WorldSpaceUiHighlight`s and shows them on hovered or focused surfaces.
+fn update_highlights(
+    mut commands: Commands,
+    new_highlights: Query<(Entity, &WorldSpaceUiHighlight, &Aabb), Without<HighlightFrame>>,
+    highlights: Query<(
+        Entity,
+        &WorldSpaceUiHighlight,
+        &HighlightFrame,
+        Has<WorldSpaceUiHit>,
+    )>,
+    mut frames: Query<&mut Visibility>,
+    focus: Res<WorldSpaceUiFocus>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for (entity, highlight, aabb) in new_highlights.iter() {
+        // Frame the face of the AABB's thinnest axis, like `planar_uv`.
+        let extents = aabb.half_extents;
+        let (size, rotation) = if extents.z <= extents.x && extents.z <= extents.y {
+            (Vec2::new(extents.x, extents.y), Quat::IDENTITY)
+        } else if extents.y <= extents.x {
+            (
+                Vec2::new(extents.x, extents.z),
+                Quat::from_rotation_x(-FRAC_PI_2),
+            )
+        } else {
+            (
+                Vec2::new(extents.z, extents.y),
+                Quat::from_rotation_y(FRAC_PI_2),
+            )
+        };
+        let frame = commands
+            .spawn((
+                Name::new("UiHighlightFrame"),
+                Mesh3d(meshes.add(ui_meshes::frame(size * 2.0, highlight.width))),
+                MeshMaterial3d(materials.add(StandardMaterial {
+                    base_color: highlight.color,
+                    unlit: true,
+                    cull_mode: None,
+                    ..default()
+                })),
+                Transform::from_translation(aabb.center.into()).with_rotation(rotation),
+                Visibility::Hidden,
+                ChildOf(entity),
+            ))
+            .id();
+        commands.entity(entity).insert(HighlightFrame(frame));
+    }
+
+    for (entity, highlight, frame, hovered) in highlights.iter() {
+        let Ok(mut visibility) = frames.get_mut(frame.0) else {
+            continue;
+        };
+        let shown =
+            (highlight.on_hover && hovered) || (highlight.on_focus && focus.0 == Some(entity));
+        visibility.set_if_neq(if shown {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+}
+
+/// Moves `WorldSpaceUiHitIndicator` dots to their surface's hit, or hides them.
+fn update_hit_indicators(
+    surfaces: Query<(
+        &WorldSpaceUiHitIndicator,
+        &WorldSpaceUiHitIndicatorEntity,
+        Option<&WorldSpaceUiHit>,
+    )>,
+    mut dots: Query<(&mut Transform, &mut Visibility)>,
+) {
+    for (indicator, dot, hit) in surfaces.iter() {
+        let Ok((mut transform, mut visibility)) = dots.get_mut(dot.0) else {
+            continue;
+        };
+        let Some(hit) = hit else {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        };
+        visibility.set_if_neq(Visibility::Inherited);
+        transform.translation = hit.world_position + hit.normal * indicator.offset;
+        transform.rotation = Quat::from_rotation_arc(Vec3::Z, hit.normal);
+    }
+}
+
+/// Moves `WorldSpaceUiCursorSprite` nodes to the pointer of a hit surface, or hides them.
+fn update_cursor_sprites(
+    roots: Query<(
+        &WorldSpaceUiCursorSprite,
+        &CursorSpriteNode,
+        Option<&UiSurfaces>,
+    )>,
+    surfaces: Query<(
+        &WorldSpaceUiSurface,
+        &WorldSpaceUiRenderTarget,
+        &PreviousCursorPosition,
+        &WorldSpaceUiHit,
+    )>,
+    pointers: Query<(&PointerId, &PointerPress)>,
+    hover_map: Res<HoverMap>,
+    interactive: Query<(), With<Interaction>>,
+    mut nodes: Query<(&mut Node, &mut ImageNode, &mut Visibility)>,
+) {
+    for (cursor, cursor_node, root_surfaces) in roots.iter() {
+        let Ok((mut node, mut image, mut visibility)) = nodes.get_mut(cursor_node.0) else {
+            continue;
+        };
+        // Overlapping surfaces can all be hit, and the nearest one is seen.
+        let hit = root_surfaces
+            .into_iter()
+            .flat_map(|root_surfaces| root_surfaces.iter())
+            .filter_map(|surface| surfaces.get(surface).ok())
+            .min_by(|(.., a), (.., b)| a.distance.total_cmp(&b.distance));
+        let Some((surface, render_target, cursor_last, _)) = hit else {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        };
+        visibility.set_if_neq(Visibility::Inherited);
+
+        // Root nodes are laid out relative to the root's viewport.
+        let viewport_min = render_target.viewport.map_or(Vec2::ZERO, |viewport| {
+            viewport.min.as_vec2() / render_target.scale_factor
+        });
+        let position = cursor_last.0 - viewport_min - cursor.hotspot;
+        node.left = Val::Px(position.x);
+        node.top = Val::Px(position.y);
+
+        let pressed = pointers
+            .iter()
+            .any(|(id, press)| *id == surface.pointer_id && press.is_primary_pressed());
+        let hovered = hover_map
+            .get(&surface.pointer_id)
+            .is_some_and(|hovered| hovered.keys().any(|&entity| interactive.contains(entity)));
+        let handle = match (&cursor.pressed_image, &cursor.hovered_image) {
+            (Some(pressed_image), _) if pressed => pressed_image,
+            (_, Some(hovered_image)) if hovered => hovered_image,
+            _ => &cursor.image,
+        };
+        if image.image != *handle {
+            image.image = handle.clone();
+        }
+    }
+}
+
+/// Sets the cursor icon of windows pointing at nodes with a `HoverCursorIcon`, restoring each
+/// window's own icon when they stop.
+fn sync_window_cursor_icons(
+    mut commands: Commands,
+    surfaces: Query<
+        (&WorldSpaceUiSurface, &HitWindow, Option<&HoverCursorIcon>),
+        With<WorldSpaceUiHit>,
+    >,
+    nodes: Query<(Option<&HoverCursorIcon>, Has<Interaction>)>,
+    hover_map: Res<HoverMap>,
+    window_icons: Query<Option<&CursorIcon>>,
+    mut original_icons: Local<HashMap<Entity, Option<CursorIcon>>>,
+) {
+    let mut icons = HashMap::<Entity, CursorIcon>::default();
+    for (surface, hit_window, surface_icon) in surfaces.iter() {
+        let (Some(window), Some(hovered)) = (hit_window.0, hover_map.get(&surface.pointer_id))
+        else {
+            continue;
+        };
+        let icon = hovered.keys().find_map(|&entity| {
+            let (node_icon, interactive) = nodes.get(entity).ok()?;
+            node_icon.or(surface_icon.filter(|_| interactive))
+        });
+        if let Some(icon) = icon {
+            icons.entry(window).or_insert_with(|| icon.0.clone());
+        }
+    }
+
+    for (&window, icon) in &icons {
+        let Ok(current) = window_icons.get(window) else {
+            continue;
+        };
+        original_icons
+            .entry(window)
+            .or_insert_with(|| current.cloned());
+        if current != Some(icon) {
+            commands.entity(window).insert(icon.clone());
+        }
+    }
+    original_icons.retain(|&window, original| {
+        if icons.contains_key(&window) {
+            return true;
+        }
+        match original.take() {
+            Some(icon) => commands.entity(window).try_insert(icon),
+            None => commands.entity(window).try_remove::<CursorIcon>(),
+        };
+        false
+    });
+}
+
+/// Points each shared UI camera at the viewport of the next `ShareUiCamera` root in turn, or
+/// of a hovered root, and hides the other roots from it.
+fn schedule_shared_ui_cameras(
+    shared: Res<SharedUiCameras>,
+    mut roots: Query<
+        (
+            Entity,
+            &WorldSpaceUiRoot,
+            &UiTargetCamera,
+            &mut Visibility,
+            Option<&UiSurfaces>,
+        ),
+        With<ShareUiCamera>,
+    >,
+    hits: Query<(), With<WorldSpaceUiHit>>,
+    mut cameras: Query<&mut Camera>,
+    mut turns: Local<HashMap<Entity, usize>>,
+    mut group: Local<Vec<(Entity, URect, bool)>>,
+) {
+    for &shared_camera in shared.0.values() {
+        group.clear();
+        group.extend(
+            roots
+                .iter()
+                .filter(|(_, _, ui_camera, ..)| ui_camera.0 == shared_camera)
+                .filter_map(|(entity, root, _, _, surfaces)| {
+                    let hovered = surfaces.is_some_and(|surfaces| {
+                        surfaces.iter().any(|surface| hits.contains(surface))
+                    });
+                    Some((entity, root.viewport?, hovered))
+                }),
+        );
+        group.sort_by_key(|(entity, ..)| *entity);
+        let Ok(mut camera) = cameras.get_mut(shared_camera) else {
+            continue;
+        };
+        if camera.is_active == group.is_empty() {
+            camera.is_active = !group.is_empty();
+        }
+        if group.is_empty() {
+            continue;
+        }
+        let turn = turns.entry(shared_camera).or_default();
+        let scheduled = match group.iter().find(|(.., hovered)| *hovered) {
+            Some(root) => *root,
+            None => {
+                *turn = (*turn + 1) % group.len();
+                group[*turn]
+            }
+        };
+        let (scheduled, viewport, _) = scheduled;
+        if camera.viewport.as_ref().is_none_or(|current| {
+            current.physical_position != viewport.min || current.physical_size != viewport.size()
+        }) {
+            camera.viewport = Some(Viewport {
+                physical_position: viewport.min,
+                physical_size: viewport.size(),
+                ..default()
+            });
+        }
+        for &(entity, ..) in group.iter() {
+            if let Ok((.., mut visibility, _)) = roots.get_mut(entity) {
+                visibility.set_if_neq(if entity == scheduled {
+                    Visibility::Inherited
+                } else {
+                    Visibility::Hidden
+                });
+            }
+        }
+    }
+    turns.retain(|camera, _| shared.0.values().any(|shared| shared == camera));
+}
+
+/// Paths of the `bevy_diagnostic` measurements of world space UI, updated every frame.
+pub struct WorldSpaceUiDiagnostics;
+impl WorldSpaceUiDiagnostics {
+    /// Pointer rays processed by mesh raycasting, or 0 on frames it is skipped as idle.
+    pub const RAYS_PROCESSED: DiagnosticPath = DiagnosticPath::const_new("world_space_ui/rays");
+    /// Surface meshes cast against in detail, summed over rays.
+    pub const SURFACES_RAYCAST: DiagnosticPath =
+        DiagnosticPath::const_new("world_space_ui/surfaces_raycast");
+    /// Inputs sent to the virtual pointers of surfaces.
+    pub const POINTER_EVENTS: DiagnosticPath =
+        DiagnosticPath::const_new("world_space_ui/pointer_events");
+    /// UI cameras of roots that render this frame.
+    pub const ACTIVE_UI_CAMERAS: DiagnosticPath =
+        DiagnosticPath::const_new("world_space_ui/active_ui_cameras");
+    /// Memory of root textures, in MiB.
+    pub const TEXTURE_MEMORY: DiagnosticPath =
+        DiagnosticPath::const_new("world_space_ui/texture_memory");
+}
+
+/// Measures the pointer inputs, UI cameras, and textures of world space UI.
+fn measure_world_space_ui(
+    mut diagnostics: Diagnostics,
+    mut pointer_inputs: MessageReader<PointerInput>,
+    surfaces: Query<&WorldSpaceUiSurface>,
+    roots: Query<(&WorldSpaceUiRoot, &UiTargetCamera)>,
+    cameras: Query<&Camera>,
+    images: Res<Assets<Image>>,
+    mut pointer_ids: Local<HashSet<PointerId>>,
+    mut seen: Local<HashSet<Entity>>,
+    mut textures: Local<HashSet<AssetId<Image>>>,
+) {
+    pointer_ids.clear();
+    pointer_ids.extend(surfaces.iter().map(|surface| surface.pointer_id));
+    let pointer_events = pointer_inputs
+        .read()
+        .filter(|input| pointer_ids.contains(&input.pointer_id))
+        .count();
+    diagnostics.add_measurement(&WorldSpaceUiDiagnostics::POINTER_EVENTS, || {
+        pointer_events as f64
+    });
+
+    // Roots may share cameras and textures, so count each once.
+    seen.clear();
+    textures.clear();
+    let mut active_cameras = 0;
+    let mut total_bytes = 0;
+    for (root, ui_camera) in roots.iter() {
+        if seen.insert(ui_camera.0) && cameras.get(ui_camera.0).is_ok_and(|c| c.is_active) {
+            active_cameras += 1;
+        }
+        if textures.insert(root.texture.id())
+            && let Some(image) = images.get(&root.texture)
+        {
+            total_bytes += texture_bytes(image);
+        }
+    }
+    diagnostics.add_measurement(&WorldSpaceUiDiagnostics::ACTIVE_UI_CAMERAS, || {
+        active_cameras as f64
+    });
+    diagnostics.add_measurement(&WorldSpaceUiDiagnostics::TEXTURE_MEMORY, || {
+        total_bytes as f64 / (1024.0 * 1024.0)
+    });
+}
+
+/// GPU memory of a texture, in bytes.
+fn texture_bytes(image: &Image) -> u64 {
+    let descriptor = &image.texture_descriptor;
+    let size = descriptor.size;
+    let texels: u64 = (0..descriptor.mip_level_count)
+        .map(|level| {
+            (size.width >> level).max(1) as u64
+                * (size.height >> level).max(1) as u64
+                * size.depth_or_array_layers as u64
+        })
+        .sum();
+    texels
+        * descriptor.format.block_copy_size(None).unwrap_or_default() as u64
+        * descriptor.sample_count as u64
+}
+
+/// Downscales, then deactivates, the least recently viewed hidden roots while root textures
+/// exceed `WorldSpaceUiSettings::texture_memory_budget`, and restores roots when viewed.
+/// Roots rendering into a viewport share their texture and are left alone.
+fn enforce_texture_budget(
+    mut commands: Commands,
+    settings: Res<WorldSpaceUiSettings>,
+    time: Res<Time>,
+    roots: Query<(
+        Entity,
+        &WorldSpaceUiRoot,
+        &UiTargetCamera,
+        Option<&UiSurfaces>,
+        Option<&UiBudgeted>,
+        Option<&WorldSpaceUiScene3dCamera>,
+    )>,
+    visibility: Query<&ViewVisibility>,
+    images: Res<Assets<Image>>,
+    mut cameras: Query<&mut Camera>,
+    mut camera_targets: Query<&mut RenderTarget>,
+    mut render_targets: Query<&mut WorldSpaceUiRenderTarget>,
+    mut resizes: MessageWriter<ResizeWorldSpaceUi>,
+    mut events: MessageWriter<WorldSpaceUiBudgetEvent>,
+    mut last_viewed: Local<HashMap<Entity, f32>>,
+) {
+    /// Textures are deactivated rather than downscaled below this many pixels on a side.
+    const MIN_SIZE: u32 = 64;
+    let Some(budget) = settings.texture_memory_budget else {
+        return;
+    };
+    let now = time.elapsed_secs();
+    last_viewed.retain(|root, _| roots.contains(*root));
+
+    // Keeps the layout of `root` at `ratio` of its original resolution by scaling its render
+    // target to match.
+    let mut rescale = |root: &WorldSpaceUiRoot,
+                       ui_camera: Entity,
+                       surfaces: &mut dyn Iterator<Item = Entity>,
+                       ratio: f32| {
+        let scale_factor = root.scale_factor * root.supersample * ratio;
+        if let Ok(mut target) = camera_targets.get_mut(ui_camera)
+            && let RenderTarget::Image(image_target) = &mut *target
+        {
+            image_target.scale_factor = scale_factor;
+        }
+        for surface in surfaces {
+            if let Ok(mut render_target) = render_targets.get_mut(surface) {
+                render_target.scale_factor = scale_factor;
+            }
+        }
+    };
+
+    let mut total = 0;
+    let mut evict = None;
+    for (entity, root, ui_camera, surfaces, budgeted, scene_camera) in roots.iter() {
+        let Some(image) = images.get(&root.texture) else {
+            continue;
+        };
+        total += texture_bytes(image);
+        if root.viewport.is_some() {
+            continue;
+        }
+        let visible = surfaces.is_some_and(|surfaces| {
+            surfaces
+                .iter()
+                .any(|surface| visibility.get(surface).is_ok_and(|v| v.get()))
+        });
+        if visible {
+            last_viewed.insert(entity, now);
+            if let Some(budgeted) = budgeted {
+                let original = budgeted.original;
+                let mut surfaces = surfaces.into_iter().flat_map(|surfaces| surfaces.iter());
+                rescale(root, ui_camera.0, &mut surfaces, 1.0);
+                resizes.write(ResizeWorldSpaceUi {
+                    root: entity,
+                    size: original,
+                });
+                commands.entity(entity).remove::<UiBudgeted>();
+                // Unbudgeted roots may leave `update_ui_camera_activity`, which would then never
+                // reactivate their cameras.
+                if budgeted.deactivated {
+                    let scene_camera = scene_camera.map(|camera| camera.0);
+                    for camera in core::iter::once(ui_camera.0).chain(scene_camera) {
+                        if let Ok(mut camera) = cameras.get_mut(camera) {
+                            camera.is_active = true;
+                        }
+                    }
+                }
+                events.write(WorldSpaceUiBudgetEvent {
+                    root: entity,
+                    action: BudgetAction::Restored,
+                });
+            }
+        } else if budgeted.is_none_or(|budgeted| !budgeted.deactivated) {
+            let viewed = last_viewed
+                .get(&entity)
+                .copied()
+                .unwrap_or(f32::NEG_INFINITY);
+            if evict.is_none_or(|(_, oldest)| viewed < oldest) {
+                evict = Some((entity, viewed));
+            }
+        }
+    }
+    if total <= budget {
+        return;
+    }
+    let Some(Ok((entity, root, ui_camera, surfaces, budgeted, _))) =
+        evict.map(|(entity, _)| roots.get(entity))
+    else {
+        return;
+    };
+    let Some(image) = images.get(&root.texture) else {
+        return;
+    };
+    let old = image.size();
+    let original = budgeted.map_or(old, |budgeted| budgeted.original);
+    let (size, action) = if old.min_element() / 2 >= MIN_SIZE {
+        let new = old / 2;
+        (new, BudgetAction::Downscaled { old, new })
+    } else {
+        (UVec2::ONE, BudgetAction::Deactivated)
+    };
+    let mut surfaces = surfaces.into_iter().flat_map(|surfaces| surfaces.iter());
+    rescale(
+        root,
+        ui_camera.0,
+        &mut surfaces,
+        size.x as f32 / original.x.max(1) as f32,
+    );
+    resizes.write(ResizeWorldSpaceUi { root: entity, size });
+    commands.entity(entity).insert(UiBudgeted {
+        original,
+        deactivated: action == BudgetAction::Deactivated,
+    });
+    events.write(WorldSpaceUiBudgetEvent {
+        root: entity,
+        action,
+    });
+}
+
+/// Activates or deactivates UI cameras of roots that control when they render.
+/// A camera is active only if every setting on its root allows rendering this frame.
+fn update_ui_camera_activity(
+    mut roots: Query<
+        (
+            Entity,
+            &UiTargetCamera,
+            Option<&mut RenderOnDemand>,
+            Option<(&UiRefreshRate, &mut TimeSinceRender)>,
+            Has<PauseWhenHidden>,
+            Option<&UiSurfaces>,
+            Option<&WorldSpaceUiScene3dCamera>,
+            Option<&UiLodPaused>,
+            Option<&UiBudgeted>,
+        ),
+        (
+            Or<(
+                With<RenderOnDemand>,
+                With<UiRefreshRate>,
+                With<PauseWhenHidden>,
+                With<UiLodPaused>,
+                With<UiBudgeted>,
+            )>,
+            Without<ShareUiCamera>,
+        ),
+    >,
+    visibility: Query<&ViewVisibility>,
+    children: Query<&Children>,
+    changed_nodes: Query<(), UiChanged>,
+    mut cameras: Query<&mut Camera>,
+    time: Res<Time>,
+) {
+    for (
+        root,
+        ui_camera,
+        mut on_demand,
+        mut refresh,
+        pause_when_hidden,
+        surfaces,
+        scene_camera,
+        lod_paused,
+        budgeted,
+    ) in roots.iter_mut()
+    {
+        let mut active = lod_paused.is_none_or(|lod_paused| !lod_paused.0)
+            && budgeted.is_none_or(|budgeted| !budgeted.deactivated);
+        if pause_when_hidden {
+            active &= surfaces.is_some_and(|surfaces| {
+                surfaces.iter().any(|surface| {
+                    visibility
+                        .get(surface)
+                        .is_ok_and(|visibility| visibility.get())
+                })
+            });
+        }
+        if let Some(on_demand) = on_demand.as_mut() {
+            on_demand.dirty |= changed_nodes.contains(root)
+                || children
+                    .iter_descendants(root)
+                    .any(|node| changed_nodes.contains(node));
+            active &= on_demand.dirty;
+        }
+        if let Some((rate, since_render)) = refresh.as_mut() {
+            since_render.0 += time.delta_secs();
+            active &= since_render.0 * rate.0 >= 1.0;
+        }
+
+        // The scene camera clears the texture, so it must not render without the UI on top.
+        if let Some(scene_camera) = scene_camera
+            && let Ok(mut camera) = cameras.get_mut(scene_camera.0)
+        {
+            camera.is_active = active;
+        }
+        let Ok(mut camera) = cameras.get_mut(ui_camera.0) else {
+            continue;
+        };
+        camera.is_active = active;
+        if active {
+            if let Some(on_demand) = on_demand.as_mut() {
+                on_demand.dirty = false;
+            }
+            if let Some((_, since_render)) = refresh.as_mut() {
+                since_render.0 = 0.0;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::app::Update;
+
+    use super::*;
+
+    #[test]
+    fn dedup_orders_strictly_increase() {
+        let mut world = World::new();
+        let (a, b) = (world.spawn_empty().id(), world.spawn_empty().id());
+        let (a, b) = (a.min(b), a.max(b));
+        let camera = world.spawn_empty().id();
+        // The lower root entity asks for the higher order.
+        let mut group = [(1, a, camera), (0, b, camera)];
+        dedup_orders(&mut group);
+        assert_eq!(
+            group.map(|(order, root, _)| (root, order)),
+            [(b, 0), (a, 1)]
+        );
+
+        let mut group = [(0, a, camera), (0, b, camera), (1, camera, camera)];
+        dedup_orders(&mut group);
+        assert_eq!(group.map(|(order, ..)| order), [0, 1, 2]);
+    }
+
+    #[test]
+    fn custom_material_is_kept() {
+        let mut app = App::new();
+        app.init_resource::<Assets<StandardMaterial>>()
+            .init_resource::<Time>()
+            .add_systems(Update, (sync_material_settings, animate_surface_fades));
+        let built = StandardMaterial {
+            base_color: Color::srgb(0.2, 0.4, 0.6),
+            unlit: true,
+            ..default()
+        };
+        let material = app
+            .world_mut()
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(built.clone());
+        let surface = app
+            .world_mut()
+            .spawn((
+                WorldSpaceUiSurface {
+                    material: WorldSpaceUiMaterial::Custom,
+                    ..default()
+                },
+                MeshMaterial3d(material.clone()),
+                WorldSpaceUiFade::fade_in(1.0),
+            ))
+            .id();
+
+        app.update();
+        let kept = app
+            .world()
+            .get::<MeshMaterial3d<StandardMaterial>>(surface)
+            .unwrap();
+        assert_eq!(kept.0, material);
+        let kept = app
+            .world()
+            .resource::<Assets<StandardMaterial>>()
+            .get(&material)
+            .unwrap();
+        assert_eq!(kept.base_color, built.base_color);
+        assert_eq!(kept.emissive, built.emissive);
+    }
+
+    #[test]
+    fn cursor_sprite_follows_nearest_overlapping_surface() {
+        let mut app = App::new();
+        app.init_resource::<HoverMap>()
+            .add_systems(Update, update_cursor_sprites);
+        let root = app
+            .world_mut()
+            .spawn(WorldSpaceUiCursorSprite {
+                hotspot: Vec2::ZERO,
+                ..default()
+            })
+            .id();
+        let render_target = WorldSpaceUiRenderTarget {
+            target: NormalizedRenderTarget::Image(ImageRenderTarget {
+                handle: Handle::default(),
+                scale_factor: 1.0,
+            }),
+            size: UVec2::splat(100),
+            viewport: None,
+            scale_factor: 1.0,
+            uv_rect: Rect::new(0.0, 0.0, 1.0, 1.0),
+        };
+        // The farther surface is spawned first, so it is first among the root's surfaces.
+        for (position, distance) in [(Vec2::splat(10.0), 5.0), (Vec2::splat(90.0), 2.0)] {
+            app.world_mut().spawn((
+                WorldSpaceUiSurface::default(),
+                WorldSpaceUiLazy,
+                render_target.clone(),
+                PreviousCursorPosition(position),
+                WorldSpaceUiHit {
+                    pointer: PointerId::Mouse,
+                    uv: position / 100.0,
+                    position,
+                    world_position: Vec3::ZERO,
+                    normal: Vec3::Z,
+                    distance,
+                },
+                UiSurfaceOf(root),
+            ));
+        }
+
+        app.update();
+        let cursor = app.world().get::<CursorSpriteNode>(root).unwrap().0;
+        let node = app.world().get::<Node>(cursor).unwrap();
+        assert_eq!((node.left, node.top), (Val::Px(90.0), Val::Px(90.0)));
+    }
+
+    #[test]
+    fn deactivated_root_resumes_rendering_when_visible() {
+        let mut app = App::new();
+        app.init_resource::<Assets<Image>>()
+            .init_resource::<Time>()
+            .init_resource::<SharedUiCameras>()
+            .insert_resource(WorldSpaceUiSettings {
+                texture_memory_budget: Some(0),
+                ..default()
+            })
+            .add_message::<ResizeWorldSpaceUi>()
+            .add_message::<WorldSpaceUiBudgetEvent>()
+            .add_systems(
+                Update,
+                (enforce_texture_budget, update_ui_camera_activity).chain(),
+            );
+        // Too small to downscale, so the root is deactivated right away.
+        let texture =
+            app.world_mut()
+                .resource_mut::<Assets<Image>>()
+                .add(WorldSpaceUiRoot::get_ui_texture(Extent3d {
+                    width: 64,
+                    height: 64,
+                    ..default()
+                }));
+        let root = app
+            .world_mut()
+            .spawn(WorldSpaceUiRoot {
+                texture,
+                ..default()
+            })
+            .id();
+        let surface = app
+            .world_mut()
+            .spawn((UiSurfaceOf(root), ViewVisibility::HIDDEN))
+            .id();
+        let camera = app.world().get::<UiTargetCamera>(root).unwrap().0;
+        let camera_active = |app: &App| app.world().get::<Camera>(camera).unwrap().is_active;
+
+        app.update();
+        assert!(app.world().get::<UiBudgeted>(root).unwrap().deactivated);
+        assert!(!camera_active(&app));
+
+        app.world_mut()
+            .get_mut::<ViewVisibility>(surface)
+            .unwrap()
+            .set();
+        app.update();
+        assert!(app.world().get::<UiBudgeted>(root).is_none());
+        assert!(camera_active(&app));
+    }
 }
@@ -0,0 +1,271 @@
+//! Regenerates the mip chain of UI textures after their UI camera renders, for
+//! `UiTextureSettings::mipmaps`, so text doesn't shimmer when seen from afar.
+//!
+//! Cameras can only render into a single mip level, so while views are prepared, the texture
+//! view of a mipmapped target only covers its first level. Materials sample the full chain.
+use bevy::{
+    app::{App, Plugin},
+    asset::{AssetId, AssetServer, Handle},
+    camera::NormalizedRenderTarget,
+    core_pipeline::{
+        FullscreenShader,
+        core_2d::graph::{Core2d, Node2d},
+    },
+    ecs::{
+        query::QueryItem,
+        resource::Resource,
+        schedule::IntoScheduleConfigs,
+        system::{Commands, Query, Res, ResMut},
+        world::World,
+    },
+    image::Image,
+    math::UVec2,
+    platform::collections::HashMap,
+    render::{
+        Render, RenderApp, RenderStartup, RenderSystems,
+        camera::ExtractedCamera,
+        render_asset::RenderAssets,
+        render_graph::{
+            NodeRunError, RenderGraphContext, RenderGraphExt, RenderLabel, ViewNode, ViewNodeRunner,
+        },
+        render_resource::{
+            BindGroupEntries, BindGroupLayoutDescriptor, BindGroupLayoutEntries,
+            CachedRenderPipelineId, ColorTargetState, ColorWrites, FilterMode, FragmentState,
+            LoadOp, Operations, PipelineCache, RenderPassColorAttachment, RenderPassDescriptor,
+            RenderPipelineDescriptor, Sampler, SamplerBindingType, SamplerDescriptor, ShaderStages,
+            SpecializedRenderPipeline, SpecializedRenderPipelines, StoreOp, TextureFormat,
+            TextureSampleType, TextureView, TextureViewDescriptor,
+            binding_types::{sampler, texture_2d},
+        },
+        renderer::{RenderContext, RenderDevice},
+        texture::GpuImage,
+    },
+    shader::Shader,
+    utils::default,
+};
+
+const MIPMAPS_SHADER: &str = "embedded://bevy_world_space_ui/mipmaps.wgsl";
+
+/// Number of mip levels of a full chain for a texture of `size`.
+pub(crate) fn mip_level_count(size: UVec2) -> u32 {
+    u32::BITS - size.max_element().max(1).leading_zeros()
+}
+
+/// Downsamples each mip level of UI textures into the next after their UI camera renders.
+pub(crate) struct UiMipmapPlugin;
+impl Plugin for UiMipmapPlugin {
+    fn build(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app
+            .init_resource::<UiMipChains>()
+            .init_resource::<SpecializedRenderPipelines<MipmapPipeline>>()
+            .add_systems(RenderStartup, init_mipmap_pipeline)
+            .add_systems(
+                Render,
+                (
+                    prepare_mip_chains
+                        .after(RenderSystems::PrepareAssets)
+                        .before(RenderSystems::ManageViews),
+                    restore_sampled_views
+                        .after(RenderSystems::ManageViews)
+                        .before(RenderSystems::PrepareBindGroups),
+                ),
+            )
+            .add_render_graph_node::<ViewNodeRunner<MipmapNode>>(Core2d, MipmapLabel)
+            .add_render_graph_edge(Core2d, Node2d::Upscaling, MipmapLabel);
+    }
+}
+
+/// Downsamples mip levels of a UI camera's target.
+#[derive(RenderLabel, Debug, Clone, PartialEq, Eq, Hash)]
+struct MipmapLabel;
+
+/// Views of the mip levels of textures rendered to this frame, by texture.
+#[derive(Resource, Default)]
+struct UiMipChains(HashMap<AssetId<Image>, MipChain>);
+
+struct MipChain {
+    /// View of the whole chain, sampled by materials.
+    sampled: TextureView,
+    /// View of each level, rendered to.
+    levels: Vec<TextureView>,
+    pipeline: CachedRenderPipelineId,
+}
+
+#[derive(Resource)]
+struct MipmapPipeline {
+    layout: BindGroupLayoutDescriptor,
+    sampler: Sampler,
+    fullscreen_shader: FullscreenShader,
+    fragment_shader: Handle<Shader>,
+}
+impl SpecializedRenderPipeline for MipmapPipeline {
+    type Key = TextureFormat;
+
+    fn specialize(&self, format: Self::Key) -> RenderPipelineDescriptor {
+        RenderPipelineDescriptor {
+            label: Some("world_space_ui_mipmap_pipeline".into()),
+            layout: vec![self.layout.clone()],
+            vertex: self.fullscreen_shader.to_vertex_state(),
+            fragment: Some(FragmentState {
+                shader: self.fragment_shader.clone(),
+                targets: vec![Some(ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+                ..default()
+            }),
+            ..default()
+        }
+    }
+}
+
+fn init_mipmap_pipeline(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    fullscreen_shader: Res<FullscreenShader>,
+    asset_server: Res<AssetServer>,
+) {
+    let layout = BindGroupLayoutDescriptor::new(
+        "world_space_ui_mipmap_layout",
+        &BindGroupLayoutEntries::sequential(
+            ShaderStages::FRAGMENT,
+            (
+                texture_2d(TextureSampleType::Float { filterable: true }),
+                sampler(SamplerBindingType::Filtering),
+            ),
+        ),
+    );
+    let sampler = render_device.create_sampler(&SamplerDescriptor {
+        label: Some("world_space_ui_mipmap_sampler"),
+        mag_filter: FilterMode::Linear,
+        min_filter: FilterMode::Linear,
+        ..default()
+    });
+    commands.insert_resource(MipmapPipeline {
+        layout,
+        sampler,
+        fullscreen_shader: fullscreen_shader.clone(),
+        fragment_shader: asset_server.load(MIPMAPS_SHADER),
+    });
+}
+
+/// Makes the texture views of mipmapped camera targets cover only their first level, so the
+/// cameras can render into them.
+fn prepare_mip_chains(
+    cameras: Query<&ExtractedCamera>,
+    mut images: ResMut<RenderAssets<GpuImage>>,
+    mut chains: ResMut<UiMipChains>,
+    pipeline: Res<MipmapPipeline>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<MipmapPipeline>>,
+    pipeline_cache: Res<PipelineCache>,
+) {
+    chains.0.clear();
+    for camera in cameras.iter() {
+        let Some(NormalizedRenderTarget::Image(target)) = &camera.target else {
+            continue;
+        };
+        let id = target.handle.id();
+        if chains.0.contains_key(&id) {
+            continue;
+        }
+        let Some(image) = images.get_mut(id) else {
+            continue;
+        };
+        if image.mip_level_count <= 1 {
+            continue;
+        }
+        let levels: Vec<TextureView> = (0..image.mip_level_count)
+            .map(|level| {
+                image.texture.create_view(&TextureViewDescriptor {
+                    label: Some("world_space_ui_mip_level"),
+                    base_mip_level: level,
+                    mip_level_count: Some(1),
+                    ..default()
+                })
+            })
+            .collect();
+        let sampled = core::mem::replace(&mut image.texture_view, levels[0].clone());
+        let pipeline = pipelines.specialize(&pipeline_cache, &pipeline, image.texture_format);
+        chains.0.insert(
+            id,
+            MipChain {
+                sampled,
+                levels,
+                pipeline,
+            },
+        );
+    }
+}
+
+/// Restores the full chain views once cameras have their render targets, so materials
+/// sample every level.
+fn restore_sampled_views(mut images: ResMut<RenderAssets<GpuImage>>, chains: Res<UiMipChains>) {
+    for (id, chain) in chains.0.iter() {
+        if let Some(image) = images.get_mut(*id) {
+            image.texture_view = chain.sampled.clone();
+        }
+    }
+}
+
+/// Downsamples each mip level of the camera's target into the next, after the camera has
+/// rendered the first.
+#[derive(Default)]
+struct MipmapNode;
+impl ViewNode for MipmapNode {
+    type ViewQuery = &'static ExtractedCamera;
+
+    fn run<'w>(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext<'w>,
+        camera: QueryItem<'w, '_, Self::ViewQuery>,
+        world: &'w World,
+    ) -> Result<(), NodeRunError> {
+        let Some(NormalizedRenderTarget::Image(target)) = &camera.target else {
+            return Ok(());
+        };
+        let Some(chain) = world.resource::<UiMipChains>().0.get(&target.handle.id()) else {
+            return Ok(());
+        };
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let mipmap_pipeline = world.resource::<MipmapPipeline>();
+        // The pipeline compiles in the background, and the chain stays blank until it does.
+        let Some(pipeline) = pipeline_cache.get_render_pipeline(chain.pipeline) else {
+            return Ok(());
+        };
+        let layout = pipeline_cache.get_bind_group_layout(&mipmap_pipeline.layout);
+        for level in chain.levels.windows(2) {
+            let [source, destination] = level else {
+                continue;
+            };
+            let bind_group = render_context.render_device().create_bind_group(
+                "world_space_ui_mipmap_bind_group",
+                &layout,
+                &BindGroupEntries::sequential((source, &mipmap_pipeline.sampler)),
+            );
+            let mut pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+                label: Some("world_space_ui_mipmap_pass"),
+                color_attachments: &[Some(RenderPassColorAttachment {
+                    view: destination,
+                    depth_slice: None,
+                    resolve_target: None,
+                    ops: Operations {
+                        load: LoadOp::Clear(default()),
+                        store: StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            pass.set_render_pipeline(pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.draw(0..3, 0..1);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,11 @@
+// Downsamples one mip level of a UI texture into the next, for `UiTextureSettings::mipmaps`.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+@group(0) @binding(0) var source: texture_2d<f32>;
+@group(0) @binding(1) var source_sampler: sampler;
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    // Each pixel is centered between four source texels, so linear sampling averages them.
+    return textureSample(source, source_sampler, in.uv);
+}
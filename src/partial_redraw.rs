@@ -0,0 +1,284 @@
+//! Restricts the UI cameras of `PartialRedraw` roots to the texture region covering changed
+//! nodes. Layout and picking still see the whole viewport: only the extracted camera and views
+//! are narrowed, and their projections zoomed so nodes land on the same pixels.
+//! The camera's intermediate texture is still cleared in full, but only the dirty region is
+//! written to the UI texture.
+use bevy::{
+    app::{App, Plugin},
+    camera::{Camera, CameraOutputMode, ClearColorConfig, Viewport},
+    ecs::{
+        component::Component,
+        entity::Entity,
+        hierarchy::Children,
+        query::{Changed, Or, Without},
+        schedule::IntoScheduleConfigs,
+        system::{Commands, Query},
+    },
+    math::{Mat4, Rect, URect, UVec2, UVec4, Vec2},
+    platform::collections::HashSet,
+    render::{
+        Render, RenderApp, RenderSystems,
+        camera::ExtractedCamera,
+        extract_component::{ExtractComponent, ExtractComponentPlugin},
+        view::ExtractedView,
+    },
+    ui::{ComputedNode, UiGlobalTransform, UiTargetCamera},
+    ui_render::UiCameraView,
+};
+
+use crate::{PartialRedraw, ShareUiCamera, UiChanged, WorldSpaceUiScene3dCamera};
+
+/// Extracts dirty regions of UI cameras and narrows their rendering to them.
+pub(crate) struct PartialRedrawPlugin;
+impl Plugin for PartialRedrawPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(ExtractComponentPlugin::<UiDirtyRect>::default());
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app.add_systems(
+            Render,
+            restrict_to_dirty_rects.before(RenderSystems::ManageViews),
+        );
+    }
+}
+
+/// Region of its target that a UI camera redraws this frame, in physical pixels.
+/// `None` redraws the whole viewport.
+#[derive(Component, Debug, Clone, Copy, ExtractComponent)]
+pub(crate) struct UiDirtyRect(pub Option<URect>);
+
+/// Sets the dirty region of `PartialRedraw` roots rendering this frame to the bounds of their
+/// changed nodes, before and after the change. Roots without any are not rendered.
+pub(crate) fn update_dirty_rects(
+    mut commands: Commands,
+    mut roots: Query<
+        (
+            Entity,
+            &UiTargetCamera,
+            &mut PartialRedraw,
+            Option<&WorldSpaceUiScene3dCamera>,
+        ),
+        Without<ShareUiCamera>,
+    >,
+    nodes: Query<(&ComputedNode, &UiGlobalTransform)>,
+    changed_nodes: Query<(), Or<(UiChanged, Changed<UiGlobalTransform>)>>,
+    mut cameras: Query<&mut Camera>,
+    children: Query<&Children>,
+) {
+    for (root, ui_camera, mut partial, scene_camera) in roots.iter_mut() {
+        let Ok(camera) = cameras.get(ui_camera.0) else {
+            continue;
+        };
+        if !camera.is_active {
+            continue;
+        }
+        let Some(viewport) = camera.physical_viewport_rect() else {
+            continue;
+        };
+        let partial = &mut *partial;
+        let mut dirty = Rect::EMPTY;
+        let mut visited = HashSet::new();
+        for node in core::iter::once(root).chain(children.iter_descendants(root)) {
+            let Ok((computed, transform)) = nodes.get(node) else {
+                continue;
+            };
+            visited.insert(node);
+            if !changed_nodes.contains(node) {
+                continue;
+            }
+            let bounds = node_bounds(computed, transform, viewport.min);
+            if let Some(drawn) = partial.drawn.insert(node, bounds) {
+                dirty = dirty.union(drawn);
+            }
+            dirty = dirty.union(bounds);
+        }
+        // Despawned nodes leave their last bounds to be redrawn.
+        partial.drawn.retain(|node, drawn| {
+            let kept = visited.contains(node);
+            if !kept {
+                dirty = dirty.union(*drawn);
+            }
+            kept
+        });
+
+        // A resized or reallocated texture lost its previous render.
+        let reallocated = partial.viewport.replace(viewport) != Some(viewport);
+        let dirty = dirty.intersect(viewport.as_rect());
+        if !reallocated && dirty.is_empty() {
+            // Nothing visible changed, so the last render is kept as is.
+            for camera in core::iter::once(ui_camera.0).chain(scene_camera.map(|camera| camera.0)) {
+                if let Ok(mut camera) = cameras.get_mut(camera) {
+                    camera.is_active = false;
+                }
+            }
+            continue;
+        }
+        let dirty = (!reallocated).then(|| URect {
+            min: dirty.min.floor().as_uvec2(),
+            max: dirty.max.ceil().as_uvec2(),
+        });
+        commands.entity(ui_camera.0).insert(UiDirtyRect(dirty));
+    }
+}
+
+/// Texture bounds of a node and its outline, in physical pixels.
+fn node_bounds(computed: &ComputedNode, transform: &UiGlobalTransform, origin: UVec2) -> Rect {
+    let half = computed.size / 2. + computed.outline_width + computed.outline_offset;
+    let mut bounds = Rect::EMPTY;
+    for corner in [
+        -half,
+        Vec2::new(half.x, -half.y),
+        half,
+        Vec2::new(-half.x, half.y),
+    ] {
+        bounds = bounds.union_point(transform.transform_point2(corner) + origin.as_vec2());
+    }
+    bounds
+}
+
+/// Narrows the viewport of cameras with a dirty region to it, zooming the projections of the
+/// camera and its UI view to match so the region's pixels are drawn where they were laid out.
+fn restrict_to_dirty_rects(
+    mut cameras: Query<(
+        Entity,
+        &mut ExtractedCamera,
+        &UiDirtyRect,
+        Option<&UiCameraView>,
+    )>,
+    mut views: Query<&mut ExtractedView>,
+) {
+    for (entity, mut camera, dirty, ui_view) in cameras.iter_mut() {
+        let (Some(dirty), Some(size)) = (dirty.0, camera.physical_viewport_size) else {
+            continue;
+        };
+        let origin = camera
+            .viewport
+            .as_ref()
+            .map_or(UVec2::ZERO, |viewport| viewport.physical_position);
+        let full = URect::from_corners(origin, origin + size);
+        let dirty = dirty.intersect(full);
+        if dirty.is_empty() || dirty == full {
+            continue;
+        }
+        let depth = camera
+            .viewport
+            .as_ref()
+            .map_or(0.0..1.0, |viewport| viewport.depth.clone());
+        camera.viewport = Some(Viewport {
+            physical_position: dirty.min,
+            physical_size: dirty.size(),
+            depth,
+        });
+        // Clearing the output would clear outside the dirty region too.
+        if let CameraOutputMode::Write { clear_color, .. } = &mut camera.output_mode {
+            *clear_color = ClearColorConfig::None;
+        }
+
+        // Maps the dirty region's normalized device coordinates to the whole clip space.
+        let min = (dirty.min - origin).as_vec2() / size.as_vec2();
+        let max = (dirty.max - origin).as_vec2() / size.as_vec2();
+        let ndc_min = Vec2::new(min.x * 2. - 1., 1. - max.y * 2.);
+        let ndc_max = Vec2::new(max.x * 2. - 1., 1. - min.y * 2.);
+        let zoom = Mat4::from_scale((2. / (ndc_max - ndc_min)).extend(1.))
+            * Mat4::from_translation((-(ndc_min + ndc_max) / 2.).extend(0.));
+        for view in core::iter::once(entity).chain(ui_view.map(|ui_view| ui_view.0)) {
+            let Ok(mut view) = views.get_mut(view) else {
+                continue;
+            };
+            view.clip_from_view = zoom * view.clip_from_view;
+            if let Some(clip_from_world) = view.clip_from_world.as_mut() {
+                *clip_from_world = zoom * *clip_from_world;
+            }
+            view.viewport = UVec4::new(dirty.min.x, dirty.min.y, dirty.size().x, dirty.size().y);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{app::Update, ecs::hierarchy::ChildOf, math::Affine2, utils::default};
+
+    use super::*;
+
+    #[test]
+    fn node_bounds_include_outline_and_viewport_origin() {
+        let computed = ComputedNode {
+            size: Vec2::new(20., 10.),
+            outline_width: 2.,
+            outline_offset: 1.,
+            ..default()
+        };
+        let transform = UiGlobalTransform::from(Affine2::from_translation(Vec2::new(50., 40.)));
+        let bounds = node_bounds(&computed, &transform, UVec2::new(100, 0));
+        assert_eq!(bounds.min, Vec2::new(137., 32.));
+        assert_eq!(bounds.max, Vec2::new(163., 48.));
+    }
+
+    fn node(center: Vec2) -> (ComputedNode, UiGlobalTransform) {
+        (
+            ComputedNode {
+                size: Vec2::splat(10.),
+                ..default()
+            },
+            UiGlobalTransform::from(Affine2::from_translation(center)),
+        )
+    }
+
+    #[test]
+    fn dirty_rects_cover_changed_and_despawned_nodes() {
+        let mut app = App::new();
+        app.add_systems(Update, update_dirty_rects);
+        let camera = app
+            .world_mut()
+            .spawn(Camera {
+                viewport: Some(Viewport {
+                    physical_size: UVec2::splat(100),
+                    ..default()
+                }),
+                ..default()
+            })
+            .id();
+        // The root isn't a node itself, so changing its children doesn't redraw it whole.
+        let root = app
+            .world_mut()
+            .spawn((UiTargetCamera(camera), PartialRedraw::default()))
+            .id();
+        let moving = app
+            .world_mut()
+            .spawn((node(Vec2::splat(10.)), ChildOf(root)))
+            .id();
+        let despawned = app
+            .world_mut()
+            .spawn((node(Vec2::splat(80.)), ChildOf(root)))
+            .id();
+        let dirty = |app: &App| app.world().get::<UiDirtyRect>(camera).unwrap().0;
+        let active = |app: &App| app.world().get::<Camera>(camera).unwrap().is_active;
+
+        // The first render draws the whole viewport.
+        app.update();
+        assert_eq!(dirty(&app), None);
+        assert_eq!(
+            app.world().get::<PartialRedraw>(root).unwrap().drawn.len(),
+            2
+        );
+
+        // A moved node redraws its old and new bounds.
+        let (_, moved) = node(Vec2::new(30., 10.));
+        app.world_mut().entity_mut(moving).insert(moved);
+        app.update();
+        assert_eq!(dirty(&app), Some(URect::new(5, 5, 35, 15)));
+
+        // A despawned node redraws its last bounds, and is no longer tracked.
+        app.world_mut().despawn(despawned);
+        app.update();
+        assert_eq!(dirty(&app), Some(URect::new(75, 75, 85, 85)));
+        let partial = app.world().get::<PartialRedraw>(root).unwrap();
+        assert!(!partial.drawn.contains_key(&despawned));
+        assert!(active(&app));
+
+        // Without changes, nothing is rendered.
+        app.update();
+        assert!(!active(&app));
+    }
+}
@@ -0,0 +1,273 @@
+//! Ray-triangle intersection against surface meshes without `MeshRayCast`, so rays can be
+//! cast in parallel. Matches `MeshRayCast` hits for triangle lists.
+use bevy::{
+    asset::{AssetEvent, AssetId, Assets},
+    ecs::{entity::Entity, message::MessageReader, resource::Resource, system::ResMut},
+    math::{Ray3d, Vec2, Vec3},
+    mesh::{Mesh, PrimitiveTopology, VertexAttributeValues},
+    picking::backend::ray::RayId,
+    platform::collections::HashMap,
+    transform::components::GlobalTransform,
+};
+
+/// A ray hit on a surface mesh, in world space.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct MeshHit {
+    pub point: Vec3,
+    pub normal: Vec3,
+    /// Interpolated UV, if the mesh has UVs.
+    pub uv: Option<Vec2>,
+    /// Distance from the ray's origin.
+    pub distance: f32,
+}
+
+/// A pointer ray, the meshes it may hit, and its hits on them, nearest first.
+#[derive(Debug)]
+pub(crate) struct RayCast {
+    pub id: RayId,
+    pub ray: Ray3d,
+    pub candidates: Vec<Entity>,
+    pub hits: Vec<(Entity, MeshHit)>,
+}
+impl RayCast {
+    pub(crate) fn new(id: RayId, ray: Ray3d) -> Self {
+        Self {
+            id,
+            ray,
+            candidates: Vec::new(),
+            hits: Vec::new(),
+        }
+    }
+}
+
+/// The vertex data of a triangle list mesh needed to cast rays against it.
+#[derive(Debug, Clone)]
+pub(crate) struct RayMesh {
+    positions: Vec<Vec3>,
+    normals: Option<Vec<Vec3>>,
+    uvs: Option<Vec<Vec2>>,
+    triangles: Vec<[usize; 3]>,
+}
+impl RayMesh {
+    /// Copies the vertex data of `mesh`, or returns `None` if it isn't a triangle list with
+    /// positions.
+    pub(crate) fn new(mesh: &Mesh) -> Option<Self> {
+        if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+            return None;
+        }
+        let positions: Vec<Vec3> = mesh
+            .attribute(Mesh::ATTRIBUTE_POSITION)?
+            .as_float3()?
+            .iter()
+            .copied()
+            .map(Vec3::from)
+            .collect();
+        let normals = mesh
+            .attribute(Mesh::ATTRIBUTE_NORMAL)
+            .and_then(VertexAttributeValues::as_float3)
+            .map(|normals| normals.iter().copied().map(Vec3::from).collect());
+        let uvs = match mesh.attribute(Mesh::ATTRIBUTE_UV_0) {
+            Some(VertexAttributeValues::Float32x2(uvs)) => {
+                Some(uvs.iter().copied().map(Vec2::from).collect())
+            }
+            _ => None,
+        };
+        let triangles = match mesh.indices() {
+            Some(indices) => {
+                let indices: Vec<usize> = indices.iter().collect();
+                indices
+                    .chunks_exact(3)
+                    .map(|triangle| [triangle[0], triangle[1], triangle[2]])
+                    .collect()
+            }
+            None => (0..positions.len() / 3)
+                .map(|i| [3 * i, 3 * i + 1, 3 * i + 2])
+                .collect(),
+        };
+        Some(Self {
+            positions,
+            normals,
+            uvs,
+            triangles,
+        })
+    }
+
+    /// UV at the barycentric weights of a triangle's vertices, if the mesh has UVs.
+    pub(crate) fn uv(&self, triangle: usize, barycentric: Vec3) -> Option<Vec2> {
+        let [a, b, c] = *self.triangles.get(triangle)?;
+        let uvs = self.uvs.as_ref()?;
+        Some(uvs[a] * barycentric.x + uvs[b] * barycentric.y + uvs[c] * barycentric.z)
+    }
+}
+
+/// Vertex data of the meshes rays were cast against, so it isn't read from the mesh assets on
+/// every hit. `None` for meshes that can't be cast against.
+#[derive(Resource, Debug, Default)]
+pub(crate) struct RayMeshCache(HashMap<AssetId<Mesh>, Option<RayMesh>>);
+impl RayMeshCache {
+    /// Caches the vertex data of a mesh, if it isn't already.
+    pub(crate) fn cache(&mut self, id: AssetId<Mesh>, meshes: &Assets<Mesh>) {
+        if self.0.contains_key(&id) {
+            return;
+        }
+        if let Some(mesh) = meshes.get(id) {
+            self.0.insert(id, RayMesh::new(mesh));
+        }
+    }
+
+    /// Cached vertex data of a mesh that can be cast against.
+    pub(crate) fn get(&self, id: AssetId<Mesh>) -> Option<&RayMesh> {
+        self.0.get(&id)?.as_ref()
+    }
+}
+
+/// Drops cached vertex data of modified and removed meshes.
+pub(crate) fn invalidate_ray_mesh_cache(
+    mut mesh_events: MessageReader<AssetEvent<Mesh>>,
+    mut cache: ResMut<RayMeshCache>,
+) {
+    for event in mesh_events.read() {
+        if let AssetEvent::Modified { id } | AssetEvent::Removed { id } = event {
+            cache.0.remove(id);
+        }
+    }
+}
+
+/// Nearest hit of `ray` on the triangles of `mesh`, drawn with `transform`.
+/// Back faces are only hit if `backfaces` is set.
+pub(crate) fn ray_mesh_hit(
+    ray: Ray3d,
+    mesh: &RayMesh,
+    transform: &GlobalTransform,
+    backfaces: bool,
+) -> Option<MeshHit> {
+    let RayMesh {
+        positions,
+        normals,
+        uvs,
+        triangles,
+    } = mesh;
+
+    // Intersect in the mesh's local space, then bring the hit back to world space.
+    let affine = transform.affine();
+    let inverse = affine.inverse();
+    let origin = inverse.transform_point3(ray.origin);
+    let direction = inverse.transform_vector3(*ray.direction);
+
+    let mut nearest: Option<(f32, [usize; 3], Vec3)> = None;
+    for &triangle in triangles {
+        let [Some(&a), Some(&b), Some(&c)] = triangle.map(|i| positions.get(i)) else {
+            continue;
+        };
+        if let Some((t, barycentric)) = ray_triangle(origin, direction, a, b, c, backfaces)
+            && nearest.is_none_or(|(nearest, ..)| t < nearest)
+        {
+            nearest = Some((t, triangle, barycentric));
+        }
+    }
+    let (t, [a, b, c], barycentric) = nearest?;
+
+    let local_normal = match normals {
+        Some(normals) => {
+            normals[a] * barycentric.x + normals[b] * barycentric.y + normals[c] * barycentric.z
+        }
+        None => (positions[b] - positions[a]).cross(positions[c] - positions[a]),
+    };
+    let normal = (inverse.matrix3.transpose() * local_normal.into())
+        .normalize_or_zero()
+        .into();
+    let uv = uvs
+        .as_ref()
+        .map(|uvs| uvs[a] * barycentric.x + uvs[b] * barycentric.y + uvs[c] * barycentric.z);
+    let point = affine.transform_point3(origin + direction * t);
+    Some(MeshHit {
+        point,
+        normal,
+        uv,
+        distance: point.distance(ray.origin),
+    })
+}
+
+/// Möller-Trumbore intersection, returning the distance along `direction` and the barycentric
+/// weights of `a`, `b`, and `c`.
+fn ray_triangle(
+    origin: Vec3,
+    direction: Vec3,
+    a: Vec3,
+    b: Vec3,
+    c: Vec3,
+    backfaces: bool,
+) -> Option<(f32, Vec3)> {
+    let (ab, ac) = (b - a, c - a);
+    let p = direction.cross(ac);
+    // Positive for counterclockwise triangles facing the ray.
+    let determinant = ab.dot(p);
+    if determinant.abs() < f32::EPSILON || (!backfaces && determinant < 0.0) {
+        return None;
+    }
+    let inverse = determinant.recip();
+    let s = origin - a;
+    let u = s.dot(p) * inverse;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = s.cross(ab);
+    let v = direction.dot(q) * inverse;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let t = ac.dot(q) * inverse;
+    (t > 0.0).then_some((t, Vec3::new(1.0 - u - v, u, v)))
+}
+
+#[cfg(test)]
+mod tests {
+    use core::f32::consts::FRAC_PI_2;
+
+    use bevy::{
+        asset::Handle,
+        camera::{ImageRenderTarget, NormalizedRenderTarget},
+        math::{Dir3, Rect, UVec2},
+    };
+
+    use super::*;
+    use crate::{WorldSpaceUiRenderTarget, WorldSpaceUiSurface, hit_position, ui_meshes};
+
+    /// Rays from the center of a `curved` mesh land on the pixel drawn at their angle.
+    #[test]
+    fn curved_hits_map_to_drawn_pixels() {
+        let (radius, arc, height) = (2.0, FRAC_PI_2, 1.0);
+        let mesh = RayMesh::new(&ui_meshes::curved(radius, arc, height, 15)).unwrap();
+        let surface = WorldSpaceUiSurface::default();
+        let render_target = WorldSpaceUiRenderTarget {
+            target: NormalizedRenderTarget::Image(ImageRenderTarget {
+                handle: Handle::default(),
+                scale_factor: 1.0,
+            }),
+            size: UVec2::new(1024, 512),
+            viewport: None,
+            scale_factor: 1.0,
+            uv_rect: Rect::new(0.0, 0.0, 1.0, 1.0),
+        };
+        // The viewer sits at the center of the cylinder, `radius` in front of the mesh.
+        let center = Vec3::new(0.0, 0.0, radius);
+
+        for u in [0.07, 0.26, 0.43, 0.61, 0.94] {
+            for y in [-0.41, -0.12, 0.18, 0.37] {
+                let angle = (u - 0.5) * arc;
+                let direction = Dir3::new(Vec3::new(angle.sin(), 0.0, -angle.cos())).unwrap();
+                let ray = Ray3d::new(center + Vec3::Y * y, direction);
+                let hit = ray_mesh_hit(ray, &mesh, &GlobalTransform::IDENTITY, false)
+                    .unwrap_or_else(|| panic!("ray at u = {u}, y = {y} missed"));
+                let uv = hit.uv.unwrap();
+                let position = hit_position(&surface, &render_target, uv, None, None).unwrap();
+
+                let expected = Vec2::new(u, 0.5 - y / height) * render_target.size.as_vec2();
+                assert!(
+                    position.distance(expected) < 1.0,
+                    "ray at u = {u}, y = {y} hit {position}, expected {expected}"
+                );
+            }
+        }
+    }
+}
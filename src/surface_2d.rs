@@ -0,0 +1,191 @@
+//! World space UI surfaces in a 2D world, picked with cursor-to-world math instead of raycasts.
+use bevy::{
+    asset::Assets,
+    camera::{Camera, Camera2d, NormalizedRenderTarget, RenderTarget, primitives::Aabb},
+    ecs::{
+        component::Component, entity::Entity, lifecycle::HookContext, message::MessageWriter,
+        query::With, reflect::ReflectComponent, system::Query, world::DeferredWorld,
+    },
+    math::{Vec2, Vec3},
+    mesh::Mesh2d,
+    picking::pointer::PointerInput,
+    reflect::{Reflect, std_traits::ReflectDefault},
+    sprite::{Anchor, Sprite},
+    sprite_render::{ColorMaterial, MeshMaterial2d},
+    transform::components::GlobalTransform,
+    window::{PrimaryWindow, Window},
+};
+
+use crate::{
+    HitWindow, PanelState, PreviousCursorPosition, WorldSpaceUiRenderTarget, WorldSpaceUiSurface,
+    planar_uv, write_pointer_move,
+};
+
+/// Marks a `WorldSpaceUiSurface` as a 2D surface displayed by a `Sprite` or a `Mesh2d`.
+/// A `Mesh2d` gets a `ColorMaterial` with the surface texture, and anything else gets a sprite
+/// of it. No 3D material is created.
+/// The surface is picked by converting the cursor to 2D world coordinates with each
+/// `Camera2d` rendering to the cursor's window, so rotated and scaled surfaces work.
+/// Meshes are mapped like `Rectangle`, across their bounding box.
+#[derive(Component, Debug, Clone, Default, Reflect)]
+#[reflect(Component, Default)]
+#[component(on_add = WorldSpaceUiSurface2d::on_add)]
+pub struct WorldSpaceUiSurface2d;
+impl WorldSpaceUiSurface2d {
+    /// On component add, display the surface texture on the mesh or sprite.
+    fn on_add(mut world: DeferredWorld, context: HookContext) {
+        let Some(texture) = world
+            .get::<WorldSpaceUiSurface>(context.entity)
+            .map(|surface| surface.texture.clone())
+        else {
+            return;
+        };
+        if world.entity(context.entity).contains::<Mesh2d>() {
+            let material = world
+                .resource_mut::<Assets<ColorMaterial>>()
+                .add(ColorMaterial::from(texture));
+            world
+                .commands()
+                .entity(context.entity)
+                .insert(MeshMaterial2d(material));
+        } else if let Some(mut sprite) = world.get_mut::<Sprite>(context.entity) {
+            sprite.image = texture;
+        } else {
+            world
+                .commands()
+                .entity(context.entity)
+                .insert(Sprite::from_image(texture));
+        }
+    }
+}
+
+/// UV of a world `point` on a sprite displaying the whole texture of `render_target`,
+/// or `None` if the point is off the sprite.
+fn sprite_uv(
+    point: Vec3,
+    transform: &GlobalTransform,
+    sprite: &Sprite,
+    anchor: Option<&Anchor>,
+    render_target: &WorldSpaceUiRenderTarget,
+) -> Option<Vec2> {
+    // Undo the sprite's rotation and scale so rotated sprites are picked correctly.
+    let local = transform
+        .affine()
+        .inverse()
+        .transform_point3(point)
+        .truncate();
+    let size = sprite
+        .custom_size
+        .unwrap_or_else(|| render_target.size.as_vec2());
+    let anchor = anchor.map(Anchor::as_vec).unwrap_or_default();
+    let offset = local / size + anchor;
+    if offset.abs().cmpgt(Vec2::splat(0.5)).any() {
+        return None;
+    }
+    let mut uv = offset + Vec2::splat(0.5);
+    uv.y = 1.0 - uv.y;
+    if sprite.flip_x {
+        uv.x = 1.0 - uv.x;
+    }
+    if sprite.flip_y {
+        uv.y = 1.0 - uv.y;
+    }
+    Some(uv)
+}
+
+/// UV of a world `point` on a mesh mapped across its bounding box, or `None` if the point is
+/// outside of it.
+fn mesh_uv(point: Vec3, transform: &GlobalTransform, aabb: &Aabb) -> Option<Vec2> {
+    let local = transform
+        .affine()
+        .inverse()
+        .transform_point3(point)
+        .truncate();
+    let (min, max) = (aabb.min().truncate(), aabb.max().truncate());
+    (local.cmpge(min).all() && local.cmple(max).all()).then(|| planar_uv(point, transform, aabb))
+}
+
+/// Moves the virtual pointer of the topmost 2D surface under the cursor of each window.
+pub(crate) fn drive_2d_pointer(
+    windows: Query<(Entity, &Window)>,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform, &RenderTarget), With<Camera2d>>,
+    mut surfaces: Query<
+        (
+            Entity,
+            &WorldSpaceUiSurface,
+            &WorldSpaceUiRenderTarget,
+            &mut PreviousCursorPosition,
+            &mut HitWindow,
+            &GlobalTransform,
+            Option<&Sprite>,
+            Option<&Anchor>,
+            Option<&Aabb>,
+            Option<&PanelState>,
+        ),
+        With<WorldSpaceUiSurface2d>,
+    >,
+    mut pointer_inputs: MessageWriter<PointerInput>,
+) {
+    let primary_window = primary_window.single().ok();
+    for (camera, camera_transform, target) in cameras.iter() {
+        if !camera.is_active {
+            continue;
+        }
+        let Some(NormalizedRenderTarget::Window(window)) = target.normalize(primary_window) else {
+            continue;
+        };
+        let Ok((window_entity, window)) = windows.get(window.entity()) else {
+            continue;
+        };
+        let Some(cursor) = window.cursor_position() else {
+            continue;
+        };
+        let Ok(world_position) = camera.viewport_to_world_2d(camera_transform, cursor) else {
+            continue;
+        };
+
+        // Overlapping surfaces are drawn back to front, so only the one in front gets the cursor.
+        let topmost = surfaces
+            .iter()
+            .filter_map(
+                |(entity, surface, render_target, _, _, transform, sprite, anchor, aabb, state)| {
+                    if !PanelState::interactive(state) {
+                        return None;
+                    }
+                    let point = world_position.extend(transform.translation().z);
+                    let uv = match (sprite, aabb) {
+                        (Some(sprite), _) => {
+                            sprite_uv(point, transform, sprite, anchor, render_target)
+                        }
+                        (None, Some(aabb)) => mesh_uv(point, transform, aabb),
+                        (None, None) => None,
+                    }?;
+                    let uv = surface.uv_mode.apply(uv)?;
+                    Some((entity, transform.translation().z, uv))
+                },
+            )
+            .max_by(|(_, a, _), (_, b, _)| a.total_cmp(b));
+
+        for (entity, surface, render_target, mut cursor_last, mut hit_window, ..) in
+            surfaces.iter_mut()
+        {
+            let Some((_, _, uv)) = topmost.filter(|(topmost, ..)| *topmost == entity) else {
+                // Window input stops going to surfaces the cursor left.
+                if hit_window.0 == Some(window_entity) {
+                    hit_window.0 = None;
+                }
+                continue;
+            };
+            hit_window.0 = Some(window_entity);
+            let position = render_target.position(uv);
+            write_pointer_move(
+                &mut pointer_inputs,
+                surface.pointer_id,
+                render_target,
+                &mut cursor_last,
+                position,
+            );
+        }
+    }
+}
@@ -0,0 +1,182 @@
+//! Uniform grid of surface bounds, to find the few surfaces a pointer ray may hit without
+//! testing every surface in the world.
+use bevy::{
+    camera::primitives::Aabb,
+    ecs::entity::Entity,
+    math::{I64Vec3, IVec3, Ray3d, Vec3, Vec3A},
+    platform::collections::{HashMap, HashSet},
+    transform::components::GlobalTransform,
+};
+
+/// Buckets the world space bounds of surface meshes into grid cells.
+#[derive(Debug, Default)]
+pub(crate) struct SurfaceIndex {
+    cell_size: f32,
+    /// Range of occupied cells.
+    cell_range: (IVec3, IVec3),
+    cells: HashMap<IVec3, Vec<Entity>>,
+    /// Meshes without bounds, or with bounds too large to bucket, which any ray may hit.
+    unbounded: Vec<Entity>,
+}
+impl SurfaceIndex {
+    /// Most cells a mesh is bucketed into before it is treated as unbounded.
+    const MAX_CELLS_PER_MESH: i64 = 512;
+    /// Most cells a ray walks through.
+    const MAX_STEPS: usize = 4096;
+
+    /// Replaces the indexed meshes.
+    pub(crate) fn rebuild<'a>(
+        &mut self,
+        meshes: impl Iterator<Item = (Entity, Option<&'a Aabb>, &'a GlobalTransform)>,
+    ) {
+        self.cells.clear();
+        self.unbounded.clear();
+        let mut bounds = Vec::new();
+        for (entity, aabb, transform) in meshes {
+            match aabb {
+                Some(aabb) => bounds.push((entity, world_bounds(aabb, transform))),
+                None => self.unbounded.push(entity),
+            }
+        }
+        if bounds.is_empty() {
+            return;
+        }
+
+        // Cells about the size of an average mesh keep both buckets and ray walks short.
+        let mean_size = bounds
+            .iter()
+            .map(|(_, (min, max))| (*max - *min).max_element())
+            .sum::<f32>()
+            / bounds.len() as f32;
+        self.cell_size = mean_size.max(0.01);
+        self.cell_range = (IVec3::MAX, IVec3::MIN);
+        for (entity, (min, max)) in bounds {
+            let (low, high) = (self.cell(min), self.cell(max));
+            let span = high.as_i64vec3() - low.as_i64vec3() + I64Vec3::ONE;
+            if span.max_element() > Self::MAX_CELLS_PER_MESH
+                || span.element_product() > Self::MAX_CELLS_PER_MESH
+            {
+                self.unbounded.push(entity);
+                continue;
+            }
+            self.cell_range = (self.cell_range.0.min(low), self.cell_range.1.max(high));
+            for x in low.x..=high.x {
+                for y in low.y..=high.y {
+                    for z in low.z..=high.z {
+                        self.cells
+                            .entry(IVec3::new(x, y, z))
+                            .or_default()
+                            .push(entity);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Adds the meshes in cells along `ray` to `candidates`.
+    pub(crate) fn query(&self, ray: Ray3d, candidates: &mut HashSet<Entity>) {
+        candidates.extend(self.unbounded.iter().copied());
+        if self.cells.is_empty() {
+            return;
+        }
+        let (low, high) = self.cell_range;
+        let min = low.as_vec3() * self.cell_size;
+        let max = (high + IVec3::ONE).as_vec3() * self.cell_size;
+        let direction = *ray.direction;
+        let Some((enter, _)) = ray_box_intersection(ray.origin, direction, min, max) else {
+            return;
+        };
+
+        // Walk the cells along the ray with a 3D DDA.
+        let start = ray.origin + direction * enter;
+        let mut cell = self.cell(start).clamp(low, high);
+        let step = IVec3::new(
+            if direction.x >= 0.0 { 1 } else { -1 },
+            if direction.y >= 0.0 { 1 } else { -1 },
+            if direction.z >= 0.0 { 1 } else { -1 },
+        );
+        let boundary = (cell + step.max(IVec3::ZERO)).as_vec3() * self.cell_size;
+        let mut t_max = Vec3::select(
+            direction.cmpeq(Vec3::ZERO),
+            Vec3::INFINITY,
+            (boundary - start) / direction,
+        );
+        let t_delta = Vec3::select(
+            direction.cmpeq(Vec3::ZERO),
+            Vec3::INFINITY,
+            (self.cell_size / direction).abs(),
+        );
+        for _ in 0..Self::MAX_STEPS {
+            if let Some(entities) = self.cells.get(&cell) {
+                candidates.extend(entities.iter().copied());
+            }
+            if t_max.x <= t_max.y && t_max.x <= t_max.z {
+                cell.x += step.x;
+                t_max.x += t_delta.x;
+            } else if t_max.y <= t_max.z {
+                cell.y += step.y;
+                t_max.y += t_delta.y;
+            } else {
+                cell.z += step.z;
+                t_max.z += t_delta.z;
+            }
+            if cell.cmplt(low).any() || cell.cmpgt(high).any() {
+                break;
+            }
+        }
+    }
+
+    fn cell(&self, point: Vec3) -> IVec3 {
+        (point / self.cell_size).floor().as_ivec3()
+    }
+}
+
+/// World space bounds of a mesh's AABB.
+fn world_bounds(aabb: &Aabb, transform: &GlobalTransform) -> (Vec3, Vec3) {
+    let affine = transform.affine();
+    let center = affine.transform_point3a(aabb.center);
+    let matrix = affine.matrix3;
+    let half_extents = Vec3A::new(
+        matrix.row(0).abs().dot(aabb.half_extents),
+        matrix.row(1).abs().dot(aabb.half_extents),
+        matrix.row(2).abs().dot(aabb.half_extents),
+    );
+    (
+        (center - half_extents).into(),
+        (center + half_extents).into(),
+    )
+}
+
+/// Whether `ray` hits the oriented bounding box of a mesh with `aabb` and `transform`.
+/// Much cheaper than casting against the triangles, and rejects most misses.
+pub(crate) fn ray_hits_bounds(ray: Ray3d, aabb: &Aabb, transform: &GlobalTransform) -> bool {
+    // Test in the mesh's local space, where the box is axis aligned.
+    let inverse = transform.affine().inverse();
+    let origin = inverse.transform_point3(ray.origin);
+    let direction = inverse.transform_vector3(*ray.direction);
+    // Pad the box so flat quads, with no thickness, are still hit.
+    let half_extents = Vec3::from(aabb.half_extents) + Vec3::splat(1e-4);
+    let center = Vec3::from(aabb.center);
+    ray_box_intersection(
+        origin,
+        direction,
+        center - half_extents,
+        center + half_extents,
+    )
+    .is_some()
+}
+
+/// Distances along a ray where it enters and exits a box, if it hits the box in front of
+/// its origin.
+pub(crate) fn ray_box_intersection(
+    origin: Vec3,
+    direction: Vec3,
+    min: Vec3,
+    max: Vec3,
+) -> Option<(f32, f32)> {
+    let inverse = direction.recip();
+    let (t1, t2) = ((min - origin) * inverse, (max - origin) * inverse);
+    let enter = t1.min(t2).max_element().max(0.0);
+    let exit = t1.max(t2).min_element();
+    (enter <= exit).then_some((enter, exit))
+}
@@ -0,0 +1,217 @@
+//! Meshes with UVs laid out for displaying a UI texture.
+//! UV (0, 0) is the top left of the UI and (1, 1) the bottom right, like `Rectangle`.
+use core::f32::consts::FRAC_PI_2;
+
+use bevy::{
+    asset::RenderAssetUsages,
+    math::{UVec2, Vec2, Vec3},
+    mesh::{Indices, Mesh, PrimitiveTopology},
+};
+
+/// A strip of a cylinder around the Y axis, curving toward a viewer `radius` in front of it,
+/// e.g. for cockpit or VR menus. The center of the strip is at the origin, facing +Z.
+/// `arc` is the angle covered in radians, split into `segments` flat quads.
+///
+/// UVs are linear in angle at the vertices. Pointer hits interpolate UVs across each quad
+/// exactly like the texture is sampled, so clicks land on what is drawn; more `segments`
+/// only make the curve smoother.
+pub fn curved(radius: f32, arc: f32, height: f32, segments: u32) -> Mesh {
+    let segments = segments.max(1);
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    for i in 0..=segments {
+        let u = i as f32 / segments as f32;
+        let angle = (u - 0.5) * arc;
+        let (sin, cos) = angle.sin_cos();
+        let normal = Vec3::new(-sin, 0.0, cos);
+        for v in [0.0, 1.0] {
+            positions.push([radius * sin, (0.5 - v) * height, radius * (1.0 - cos)]);
+            normals.push(normal.to_array());
+            uvs.push([u, v]);
+        }
+    }
+    grid_mesh(positions, normals, uvs, segments, 1)
+}
+
+/// A section of the inside of a sphere, curving toward a viewer at its center `radius` in
+/// front of it, e.g. for planetarium style menus around the player. The center of the section
+/// is at the origin, facing +Z. `arc` holds the horizontal and vertical angles covered in
+/// radians, split into `segments` flat quads.
+///
+/// UVs are linear in yaw and pitch at the vertices, and hits interpolate them like `curved`.
+pub fn dome(radius: f32, arc: Vec2, segments: UVec2) -> Mesh {
+    let segments = segments.max(UVec2::ONE);
+    let center = Vec3::new(0.0, 0.0, radius);
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    for i in 0..=segments.x {
+        let u = i as f32 / segments.x as f32;
+        let yaw = (u - 0.5) * arc.x;
+        for j in 0..=segments.y {
+            let v = j as f32 / segments.y as f32;
+            let pitch = (0.5 - v) * arc.y;
+            let direction = Vec3::new(
+                yaw.sin() * pitch.cos(),
+                pitch.sin(),
+                -yaw.cos() * pitch.cos(),
+            );
+            positions.push((center + radius * direction).to_array());
+            normals.push((-direction).to_array());
+            uvs.push([u, v]);
+        }
+    }
+    grid_mesh(positions, normals, uvs, segments.x, segments.y)
+}
+
+/// A flat rectangle of `size` with rounded corners, centered at the origin and facing +Z,
+/// e.g. to match a `BorderRadius` on the UI root. `corner_radius` is in world units, so for a
+/// UI radius in pixels multiply it by `size.x / resolution.x`.
+///
+/// If `bevel` is positive, the edge slopes outward by `bevel` as it goes back by `bevel`,
+/// textured with the UI's border pixels.
+pub fn rounded_panel(size: Vec2, corner_radius: f32, bevel: f32) -> Mesh {
+    const CORNER_SEGMENTS: u32 = 8;
+    let half = size / 2.0;
+    let radius = corner_radius.clamp(0.0, half.min_element());
+    let uv = |point: Vec2| [point.x / size.x + 0.5, 0.5 - point.y / size.y];
+
+    // Corners counterclockwise from the top right, each sweeping a quarter turn.
+    let mut outline = Vec::new();
+    let corners = [
+        Vec2::new(half.x - radius, half.y - radius),
+        Vec2::new(radius - half.x, half.y - radius),
+        Vec2::new(radius - half.x, radius - half.y),
+        Vec2::new(half.x - radius, radius - half.y),
+    ];
+    for (i, center) in corners.into_iter().enumerate() {
+        for j in 0..=CORNER_SEGMENTS {
+            let angle = (i as f32 + j as f32 / CORNER_SEGMENTS as f32) * FRAC_PI_2;
+            let normal = Vec2::from_angle(angle);
+            outline.push((center + radius * normal, normal));
+        }
+    }
+
+    // The face is a fan around its center, which works since it is convex.
+    let mut positions = vec![[0.0, 0.0, 0.0]];
+    let mut normals = vec![[0.0, 0.0, 1.0]];
+    let mut uvs = vec![[0.5, 0.5]];
+    let mut indices = Vec::new();
+    let count = outline.len() as u32;
+    for (i, (point, _)) in outline.iter().enumerate() {
+        positions.push(point.extend(0.0).to_array());
+        normals.push([0.0, 0.0, 1.0]);
+        uvs.push(uv(*point));
+        let i = i as u32;
+        indices.extend([0, i + 1, (i + 1) % count + 1]);
+    }
+
+    if bevel > 0.0 {
+        let start = positions.len() as u32;
+        for (point, normal) in &outline {
+            let normal = normal.extend(1.0).normalize();
+            positions.push(point.extend(0.0).to_array());
+            positions.push(
+                (*point + normal.truncate().normalize() * bevel)
+                    .extend(-bevel)
+                    .to_array(),
+            );
+            normals.extend([normal.to_array(); 2]);
+            uvs.extend([uv(*point); 2]);
+        }
+        for i in 0..count {
+            let (front, back) = (start + 2 * i, start + 2 * i + 1);
+            let next = start + 2 * ((i + 1) % count);
+            indices.extend([front, back, next + 1]);
+            indices.extend([front, next + 1, next]);
+        }
+    }
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+/// A flat rectangular ring of `width` around a rectangle of `size`, centered at the origin
+/// and facing +Z, e.g. to outline a panel. The inner edge matches the rectangle.
+pub fn frame(size: Vec2, width: f32) -> Mesh {
+    let inner = size / 2.0;
+    let outer = inner + Vec2::splat(width.max(0.0));
+    let corners = [
+        Vec2::new(1.0, 1.0),
+        Vec2::new(-1.0, 1.0),
+        Vec2::new(-1.0, -1.0),
+        Vec2::new(1.0, -1.0),
+    ];
+    let mut positions = Vec::new();
+    let mut uvs = Vec::new();
+    for corner in corners {
+        for half in [inner, outer] {
+            let point = corner * half;
+            positions.push(point.extend(0.0).to_array());
+            uvs.push([
+                point.x / (2.0 * outer.x) + 0.5,
+                0.5 - point.y / (2.0 * outer.y),
+            ]);
+        }
+    }
+    let mut indices = Vec::new();
+    for i in 0..4 {
+        let (inner, outer) = (2 * i, 2 * i + 1);
+        let (next_inner, next_outer) = ((2 * i + 2) % 8, (2 * i + 3) % 8);
+        indices.extend([inner, outer, next_outer]);
+        indices.extend([inner, next_outer, next_inner]);
+    }
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 0.0, 1.0]; 8])
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+/// Builds a triangle list from `columns + 1` columns of `rows + 1` vertices, top to bottom.
+fn grid_mesh(
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    uvs: Vec<[f32; 2]>,
+    columns: u32,
+    rows: u32,
+) -> Mesh {
+    let mut indices = Vec::new();
+    for i in 0..columns {
+        for j in 0..rows {
+            let top_left = i * (rows + 1) + j;
+            let top_right = top_left + rows + 1;
+            let (bottom_left, bottom_right) = (top_left + 1, top_right + 1);
+            indices.extend([bottom_left, bottom_right, top_right]);
+            indices.extend([bottom_left, top_right, top_left]);
+        }
+    }
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+/// Size of the flat panel a `curved` mesh unrolls to, to match the UI aspect ratio.
+pub fn curved_size(radius: f32, arc: f32, height: f32) -> Vec2 {
+    Vec2::new(radius * arc, height)
+}
+
+/// Size of the flat panel a `dome` mesh unrolls to along its center lines.
+pub fn dome_size(radius: f32, arc: Vec2) -> Vec2 {
+    radius * arc
+}
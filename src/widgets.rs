@@ -0,0 +1,149 @@
+//! Simple interactive widgets for world space UI.
+use bevy::{
+    color::Color,
+    ecs::{
+        component::Component,
+        entity::Entity,
+        event::EntityEvent,
+        lifecycle::HookContext,
+        name::Name,
+        observer::On,
+        reflect::ReflectComponent,
+        system::{Commands, Query},
+        world::DeferredWorld,
+    },
+    picking::{
+        events::{Click, Out, Over, Pointer, Press, Release},
+        pointer::PointerId,
+    },
+    reflect::{Reflect, std_traits::ReflectDefault},
+    text::{FontSize, TextColor, TextFont},
+    ui::{AlignItems, BackgroundColor, BorderRadius, Node, UiRect, Val, widget::Text},
+    utils::default,
+};
+
+/// Background colors of a `WorldSpaceUiButton` in each state.
+#[derive(Debug, Clone, Copy, Reflect)]
+#[reflect(Default)]
+pub struct ButtonColors {
+    pub idle: Color,
+    pub hovered: Color,
+    pub pressed: Color,
+}
+impl Default for ButtonColors {
+    fn default() -> Self {
+        Self {
+            idle: Color::srgb(0.15, 0.3, 0.8),
+            hovered: Color::srgb(0.45, 0.3, 0.8),
+            pressed: Color::srgb(0.15, 0.65, 0.3),
+        }
+    }
+}
+
+/// A button with a text `label`, colored by the state of the pointers on it.
+/// Observe `WorldSpaceUiButtonClick` on it to react to clicks.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component, Default)]
+#[require(
+    Node {
+        align_items: AlignItems::Center,
+        padding: UiRect::all(Val::Px(20.)),
+        border_radius: BorderRadius::all(Val::Px(10.)),
+        ..default()
+    },
+    BackgroundColor,
+)]
+#[component(on_add = WorldSpaceUiButton::on_add)]
+pub struct WorldSpaceUiButton {
+    pub label: String,
+    pub font_size: f32,
+    pub text_color: Color,
+    pub colors: ButtonColors,
+}
+impl Default for WorldSpaceUiButton {
+    fn default() -> Self {
+        Self {
+            label: String::new(),
+            font_size: 40.,
+            text_color: Color::WHITE,
+            colors: ButtonColors::default(),
+        }
+    }
+}
+impl WorldSpaceUiButton {
+    /// A button labeled `label` with default styling.
+    pub fn new(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            ..default()
+        }
+    }
+
+    /// On component add, spawn the label and observe pointer events.
+    fn on_add(mut world: DeferredWorld, context: HookContext) {
+        let button = world.get::<Self>(context.entity).unwrap().clone();
+        if let Some(mut background) = world.get_mut::<BackgroundColor>(context.entity) {
+            background.0 = button.colors.idle;
+        }
+        world
+            .commands()
+            .entity(context.entity)
+            .observe(Self::on_over)
+            .observe(Self::on_out)
+            .observe(Self::on_press)
+            .observe(Self::on_release)
+            .observe(Self::on_click)
+            .with_child((
+                Name::new("WorldSpaceUiButtonLabel"),
+                Text::new(button.label),
+                TextFont {
+                    font_size: FontSize::Px(button.font_size),
+                    ..default()
+                },
+                TextColor(button.text_color),
+            ));
+    }
+
+    fn on_over(pointer: On<Pointer<Over>>, mut buttons: Query<(&Self, &mut BackgroundColor)>) {
+        if let Ok((button, mut background)) = buttons.get_mut(pointer.event().entity) {
+            background.0 = button.colors.hovered;
+        }
+    }
+
+    fn on_out(pointer: On<Pointer<Out>>, mut buttons: Query<(&Self, &mut BackgroundColor)>) {
+        if let Ok((button, mut background)) = buttons.get_mut(pointer.event().entity) {
+            background.0 = button.colors.idle;
+        }
+    }
+
+    fn on_press(pointer: On<Pointer<Press>>, mut buttons: Query<(&Self, &mut BackgroundColor)>) {
+        if let Ok((button, mut background)) = buttons.get_mut(pointer.event().entity) {
+            background.0 = button.colors.pressed;
+        }
+    }
+
+    fn on_release(
+        pointer: On<Pointer<Release>>,
+        mut buttons: Query<(&Self, &mut BackgroundColor)>,
+    ) {
+        if let Ok((button, mut background)) = buttons.get_mut(pointer.event().entity) {
+            background.0 = button.colors.hovered;
+        }
+    }
+
+    fn on_click(pointer: On<Pointer<Click>>, mut commands: Commands) {
+        commands.trigger(WorldSpaceUiButtonClick {
+            entity: pointer.event().entity,
+            pointer: pointer.event().pointer_id,
+        });
+    }
+}
+
+/// Triggered on a `WorldSpaceUiButton` when a pointer clicks it.
+#[derive(EntityEvent, Debug, Clone)]
+pub struct WorldSpaceUiButtonClick {
+    /// The button.
+    pub entity: Entity,
+    /// Virtual pointer of the surface it was clicked through.
+    pub pointer: PointerId,
+}
@@ -0,0 +1,333 @@
+//! Drives surface pointers with OpenXR controllers: each hand's aim pose casts a ray, and its
+//! trigger and grip press the primary and secondary buttons. Requires the `xr` feature.
+use bevy::{
+    app::{App, Plugin, PreUpdate, Startup},
+    asset::{Assets, uuid::Uuid},
+    ecs::{
+        component::Component,
+        entity::Entity,
+        error::Result,
+        hierarchy::ChildOf,
+        message::MessageWriter,
+        name::Name,
+        query::{Or, With},
+        reflect::ReflectComponent,
+        resource::Resource,
+        schedule::{IntoScheduleConfigs, common_conditions::resource_exists},
+        system::{Commands, Local, Query, Res, ResMut},
+    },
+    math::{Isometry3d, Ray3d},
+    pbr::{MeshMaterial3d, StandardMaterial},
+    picking::{
+        mesh_picking::ray_cast::{MeshRayCast, MeshRayCastSettings},
+        pointer::{Location, PointerAction, PointerButton, PointerId, PointerInput},
+    },
+    reflect::Reflect,
+    transform::components::GlobalTransform,
+};
+use bevy_mod_openxr::{
+    action_binding::{OxrSendActionBindings, OxrSuggestActionBinding},
+    action_set_attaching::OxrAttachActionSet,
+    action_set_syncing::{OxrActionSetSyncSet, OxrSyncActionSet},
+    openxr,
+    resources::OxrInstance,
+    session::OxrSession,
+};
+use bevy_mod_xr::session::{XrSessionCreated, XrTrackingRoot, session_available, session_running};
+
+use crate::{
+    PanelState, PreviousCursorPosition, UiInteractionProxyOf, WorldSpaceUiCurvature,
+    WorldSpaceUiPresses, WorldSpaceUiRenderTarget, WorldSpaceUiSettings, WorldSpaceUiSurface,
+    WorldSpaceUiSystems, hit_position, write_pointer_move,
+};
+
+/// Interaction profiles with suggested bindings, and their trigger and grip inputs.
+/// Click inputs are read as 0 or 1.
+const INTERACTION_PROFILES: [(&str, &str, Option<&str>); 5] = [
+    (
+        "/interaction_profiles/khr/simple_controller",
+        "input/select/click",
+        None,
+    ),
+    (
+        "/interaction_profiles/oculus/touch_controller",
+        "input/trigger/value",
+        Some("input/squeeze/value"),
+    ),
+    (
+        "/interaction_profiles/valve/index_controller",
+        "input/trigger/value",
+        Some("input/squeeze/value"),
+    ),
+    (
+        "/interaction_profiles/htc/vive_controller",
+        "input/trigger/value",
+        Some("input/squeeze/click"),
+    ),
+    (
+        "/interaction_profiles/microsoft/motion_controller",
+        "input/trigger/value",
+        Some("input/squeeze/click"),
+    ),
+];
+
+/// Trigger and grip values above which a button is pressed, and below which it is released.
+const PRESS_THRESHOLD: f32 = 0.75;
+const RELEASE_THRESHOLD: f32 = 0.25;
+
+/// Drives surface pointers with OpenXR controllers. Add it after the OpenXR plugins and
+/// `WorldSpaceUiPlugin`.
+#[derive(Default)]
+pub struct WorldSpaceUiXrPlugin;
+impl Plugin for WorldSpaceUiXrPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<WorldSpaceUiXrAim>()
+            .add_systems(
+                Startup,
+                (create_xr_actions, attach_xr_actions)
+                    .chain()
+                    .run_if(session_available),
+            )
+            .add_systems(
+                OxrSendActionBindings,
+                suggest_xr_bindings.run_if(resource_exists::<XrUiActions>),
+            )
+            .add_systems(
+                XrSessionCreated,
+                spawn_xr_aims.run_if(resource_exists::<XrUiActions>),
+            )
+            .add_systems(
+                PreUpdate,
+                (
+                    sync_xr_actions.before(OxrActionSetSyncSet),
+                    drive_xr_pointers
+                        .after(OxrActionSetSyncSet)
+                        .in_set(WorldSpaceUiSystems::DrivePointer),
+                )
+                    .run_if(resource_exists::<XrUiActions>)
+                    .run_if(session_running),
+            );
+    }
+}
+
+/// A controller hand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum XrHand {
+    Left,
+    Right,
+}
+impl XrHand {
+    /// Pointer of the hand in `WorldSpaceUiPresses`.
+    pub const fn pointer_id(self) -> PointerId {
+        match self {
+            Self::Left => {
+                PointerId::Custom(Uuid::from_u128(0x5d2d_52c7_9a5e_4b3f_8a1c_1f0e_7b6a_2c01))
+            }
+            Self::Right => {
+                PointerId::Custom(Uuid::from_u128(0x5d2d_52c7_9a5e_4b3f_8a1c_1f0e_7b6a_2c02))
+            }
+        }
+    }
+}
+
+/// Marks the tracked aim pose of a hand, spawned under the `XrTrackingRoot` when the session is
+/// created. Its forward direction is the pointer ray, e.g. to draw a laser along it.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct WorldSpaceUiXrAim(pub XrHand);
+
+/// OpenXR actions read for world space UI.
+#[derive(Resource)]
+struct XrUiActions {
+    set: openxr::ActionSet,
+    aim: openxr::Action<openxr::Posef>,
+    trigger: openxr::Action<f32>,
+    grip: openxr::Action<f32>,
+    /// Subaction paths of the left and right hands.
+    hands: [openxr::Path; 2],
+}
+
+fn create_xr_actions(mut commands: Commands, instance: Res<OxrInstance>) -> Result {
+    let hands = [
+        instance.string_to_path("/user/hand/left")?,
+        instance.string_to_path("/user/hand/right")?,
+    ];
+    let set = instance.create_action_set("world_space_ui", "World space UI", 0)?;
+    let aim = set.create_action("aim", "Aim", &hands)?;
+    let trigger = set.create_action("trigger", "Trigger", &hands)?;
+    let grip = set.create_action("grip", "Grip", &hands)?;
+    commands.insert_resource(XrUiActions {
+        set,
+        aim,
+        trigger,
+        grip,
+        hands,
+    });
+    Ok(())
+}
+
+fn attach_xr_actions(actions: Res<XrUiActions>, mut attach: MessageWriter<OxrAttachActionSet>) {
+    attach.write(OxrAttachActionSet(actions.set.clone()));
+}
+
+fn suggest_xr_bindings(
+    actions: Res<XrUiActions>,
+    mut bindings: MessageWriter<OxrSuggestActionBinding>,
+) {
+    for (profile, trigger, grip) in INTERACTION_PROFILES {
+        let paths = |input: &str| {
+            ["left", "right"]
+                .map(|hand| format!("/user/hand/{hand}/{input}").into())
+                .to_vec()
+        };
+        bindings.write(OxrSuggestActionBinding {
+            action: actions.aim.as_raw(),
+            interaction_profile: profile.into(),
+            bindings: paths("input/aim/pose"),
+        });
+        bindings.write(OxrSuggestActionBinding {
+            action: actions.trigger.as_raw(),
+            interaction_profile: profile.into(),
+            bindings: paths(trigger),
+        });
+        if let Some(grip) = grip {
+            bindings.write(OxrSuggestActionBinding {
+                action: actions.grip.as_raw(),
+                interaction_profile: profile.into(),
+                bindings: paths(grip),
+            });
+        }
+    }
+}
+
+fn sync_xr_actions(actions: Res<XrUiActions>, mut sync: MessageWriter<OxrSyncActionSet>) {
+    sync.write(OxrSyncActionSet(actions.set.clone()));
+}
+
+/// Spawns a tracked space following each hand's aim pose.
+fn spawn_xr_aims(
+    mut commands: Commands,
+    actions: Res<XrUiActions>,
+    session: Res<OxrSession>,
+    tracking_root: Query<Entity, With<XrTrackingRoot>>,
+) -> Result {
+    let tracking_root = tracking_root.single()?;
+    for (hand, path) in [XrHand::Left, XrHand::Right].into_iter().zip(actions.hands) {
+        let space = session.create_action_space(&actions.aim, path, Isometry3d::IDENTITY)?;
+        commands.spawn((
+            Name::new("WorldSpaceUiXrAim"),
+            WorldSpaceUiXrAim(hand),
+            space,
+            ChildOf(tracking_root),
+        ));
+    }
+    Ok(())
+}
+
+/// Moves the virtual pointer of the nearest surface hit by each hand's aim ray, and forwards the
+/// hand's trigger and grip to it as the primary and secondary buttons.
+/// Rays come from the controllers rather than either eye's camera, so both eyes see the same
+/// pointer. Hits leave the surface's `HitWindow` alone, so window clicks don't reach panels only
+/// a controller points at.
+fn drive_xr_pointers(
+    actions: Res<XrUiActions>,
+    session: Res<OxrSession>,
+    mut raycast: MeshRayCast,
+    settings: Res<WorldSpaceUiSettings>,
+    aims: Query<(&WorldSpaceUiXrAim, &GlobalTransform)>,
+    targets: Query<(), Or<(With<WorldSpaceUiSurface>, With<UiInteractionProxyOf>)>>,
+    proxies: Query<&UiInteractionProxyOf>,
+    mut surfaces: Query<(
+        &WorldSpaceUiSurface,
+        &WorldSpaceUiRenderTarget,
+        &mut PreviousCursorPosition,
+        Option<&WorldSpaceUiCurvature>,
+        Option<&MeshMaterial3d<StandardMaterial>>,
+        Option<&PanelState>,
+    )>,
+    materials: Res<Assets<StandardMaterial>>,
+    mut presses: ResMut<WorldSpaceUiPresses>,
+    mut pointer_inputs: MessageWriter<PointerInput>,
+    mut held: Local<[[bool; 2]; 2]>,
+) -> Result {
+    for (aim, transform) in aims.iter() {
+        let hand = aim.0;
+        let path = actions.hands[hand as usize];
+
+        let ray = Ray3d::new(transform.translation(), transform.forward());
+        let raycast_settings = MeshRayCastSettings {
+            visibility: settings.ray_cast_visibility,
+            filter: &|entity| targets.contains(entity),
+            early_exit_test: &|_| true,
+        };
+        let hit = raycast
+            .cast_ray(ray, &raycast_settings)
+            .first()
+            .map(|(entity, hit)| {
+                let surface = proxies.get(*entity).map_or(*entity, |proxy_of| proxy_of.0);
+                (surface, hit.clone())
+            });
+        let mut hovered = None;
+        if let Some((entity, hit)) = hit
+            && hit.distance <= settings.max_interaction_distance
+            && let Some(uv) = hit.uv
+            && let Ok((surface, render_target, mut cursor_last, curvature, material, state)) =
+                surfaces.get_mut(entity)
+            && PanelState::interactive(state)
+        {
+            let material = material.and_then(|material| materials.get(&material.0));
+            if let Some(position) = hit_position(surface, render_target, uv, curvature, material) {
+                write_pointer_move(
+                    &mut pointer_inputs,
+                    surface.pointer_id,
+                    render_target,
+                    &mut cursor_last,
+                    position,
+                );
+                hovered = Some(entity);
+            }
+        }
+
+        let values = [
+            actions.trigger.state(&session, path)?.current_state,
+            actions.grip.state(&session, path)?.current_state,
+        ];
+        for ((button, value), held) in [PointerButton::Primary, PointerButton::Secondary]
+            .into_iter()
+            .zip(values)
+            .zip(held[hand as usize].iter_mut())
+        {
+            let key = (hand.pointer_id(), button);
+            let (surface, action) = if !*held && value >= PRESS_THRESHOLD {
+                *held = true;
+                let Some(surface) = hovered else {
+                    continue;
+                };
+                presses.0.insert(key, surface);
+                (surface, PointerAction::Press(button))
+            } else if *held && value <= RELEASE_THRESHOLD {
+                *held = false;
+                // Releases reach the pressed surface even after the ray leaves it. If there was
+                // no press, or its surface has since been despawned, the release is dropped.
+                let Some(surface) = presses.0.remove(&key) else {
+                    continue;
+                };
+                (surface, PointerAction::Release(button))
+            } else {
+                continue;
+            };
+            let Ok((surface, render_target, cursor_last, ..)) = surfaces.get(surface) else {
+                continue;
+            };
+            pointer_inputs.write(PointerInput::new(
+                surface.pointer_id,
+                Location {
+                    target: render_target.target.clone(),
+                    position: cursor_last.0,
+                },
+                action,
+            ));
+        }
+    }
+    Ok(())
+}
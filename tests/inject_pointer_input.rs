@@ -0,0 +1,140 @@
+//! Headless clicks through `InjectPointerInput`.
+use bevy::{
+    asset::AssetPlugin,
+    camera::CameraPlugin,
+    image::ImagePlugin,
+    input::InputPlugin,
+    mesh::MeshPlugin,
+    picking::{
+        DefaultPickingPlugins,
+        pointer::{PointerAction, PointerButton},
+    },
+    prelude::*,
+    render::render_resource::Extent3d,
+    text::TextPlugin,
+    ui::picking_backend::UiPickingPlugin,
+    window::{ExitCondition, WindowPlugin},
+};
+use bevy_world_space_ui::prelude::*;
+
+/// UI nodes that received a `Pointer<Press>`.
+#[derive(Resource, Default)]
+struct Pressed(Vec<Entity>);
+
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        ImagePlugin::default(),
+        WindowPlugin {
+            primary_window: None,
+            exit_condition: ExitCondition::DontExit,
+            ..default()
+        },
+        InputPlugin,
+        TransformPlugin,
+        MeshPlugin,
+        CameraPlugin,
+        TextPlugin,
+        UiPlugin,
+        DefaultPickingPlugins,
+        UiPickingPlugin,
+    ))
+    .init_asset::<StandardMaterial>()
+    .add_plugins(WorldSpaceUiPlugin)
+    .init_resource::<Pressed>();
+    app
+}
+
+/// Spawns a root covered by a button and a surface displaying it, returning the surface and
+/// the button.
+fn spawn_ui(world: &mut World) -> (Entity, Entity) {
+    let texture = world
+        .resource_mut::<Assets<Image>>()
+        .add(WorldSpaceUiRoot::get_ui_texture(Extent3d {
+            width: 256,
+            height: 256,
+            ..default()
+        }));
+    let root = world
+        .spawn((
+            WorldSpaceUiRoot {
+                texture: texture.clone(),
+                ..default()
+            },
+            Node {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                ..default()
+            },
+        ))
+        .id();
+    let button = world
+        .spawn((
+            Node {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                ..default()
+            },
+            ChildOf(root),
+        ))
+        .observe(|press: On<Pointer<Press>>, mut pressed: ResMut<Pressed>| {
+            pressed.0.push(press.entity);
+        })
+        .id();
+    let surface = world
+        .spawn((
+            WorldSpaceUiSurface {
+                texture,
+                ..default()
+            },
+            UiSurfaceOf(root),
+        ))
+        .id();
+    (surface, button)
+}
+
+#[test]
+fn injected_press_reaches_ui_node() {
+    let mut app = app();
+    let (surface, button) = spawn_ui(app.world_mut());
+    // Link the surface and lay out the UI.
+    for _ in 0..3 {
+        app.update();
+    }
+
+    let uv = Vec2::splat(0.5);
+    app.world_mut().write_message(InjectPointerInput {
+        surface,
+        uv,
+        action: PointerAction::Move { delta: Vec2::ZERO },
+    });
+    for _ in 0..2 {
+        app.update();
+    }
+    app.world_mut().write_message(InjectPointerInput {
+        surface,
+        uv,
+        action: PointerAction::Press(PointerButton::Primary),
+    });
+    for _ in 0..2 {
+        app.update();
+    }
+
+    assert!(app.world().resource::<Pressed>().0.contains(&button));
+}
+
+#[test]
+fn injection_to_unlinked_surface_is_dropped() {
+    let mut app = app();
+    let surface = app.world_mut().spawn(WorldSpaceUiSurface::default()).id();
+    app.world_mut().write_message(InjectPointerInput {
+        surface,
+        uv: Vec2::splat(0.5),
+        action: PointerAction::Press(PointerButton::Primary),
+    });
+    // Doesn't panic.
+    app.update();
+    assert!(app.world().resource::<Pressed>().0.is_empty());
+}